@@ -0,0 +1,156 @@
+pub struct IterWithRemaining<I>
+where
+    I: Iterator + Clone,
+{
+    iter: I,
+}
+
+impl<I> IterWithRemaining<I>
+where
+    I: Iterator + Clone,
+{
+    pub fn new(iter: I) -> Self {
+        IterWithRemaining { iter }
+    }
+}
+
+impl<I> Iterator for IterWithRemaining<I>
+where
+    I: Iterator + Clone,
+{
+    type Item = (I::Item, I);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|item| (item, self.iter.clone()))
+    }
+}
+
+/// Every unordered pair of items from a cloneable iterator, `(a, b)` but never `(b, a)`
+pub fn pairs<I>(iter: I) -> impl Iterator<Item = (I::Item, I::Item)>
+where
+    I: Iterator + Clone,
+    I::Item: Clone,
+{
+    IterWithRemaining::new(iter)
+        .flat_map(|(first, remaining)| remaining.map(move |second| (first.clone(), second)))
+}
+
+pub fn unordered_pairs<T>(items: &[T]) -> impl Iterator<Item = (&T, &T)> {
+    pairs(items.iter())
+}
+
+/// Every ordered pair of distinct items in the slice, both `(a, b)` and `(b, a)`
+pub fn ordered_pairs<T>(items: &[T]) -> impl Iterator<Item = (&T, &T)> {
+    items
+        .iter()
+        .enumerate()
+        .flat_map(move |(first_index, first)| {
+            items
+                .iter()
+                .enumerate()
+                .filter(move |(second_index, _)| *second_index != first_index)
+                .map(move |(_, second)| (first, second))
+        })
+}
+
+pub fn combinations<T>(items: &[T], k: usize) -> impl Iterator<Item = Vec<&T>> {
+    let mut indices: Option<Vec<usize>> = (k <= items.len()).then(|| (0..k).collect());
+
+    std::iter::from_fn(move || {
+        let current = indices.as_mut()?;
+        let combination = current.iter().map(|index| &items[*index]).collect();
+
+        // Find the rightmost index that can still move right, and reset everything after it
+        match (0..k)
+            .rev()
+            .find(|&position| current[position] < items.len() - k + position)
+        {
+            Some(position) => {
+                current[position] += 1;
+                for after in position + 1..k {
+                    current[after] = current[after - 1] + 1;
+                }
+            }
+            None => indices = None,
+        }
+
+        Some(combination)
+    })
+}
+
+/// Every ordering of the items in the slice, in lexicographic order of their positions
+pub fn permutations<T>(items: &[T]) -> impl Iterator<Item = Vec<&T>> {
+    let mut indices: Option<Vec<usize>> = Some((0..items.len()).collect());
+
+    std::iter::from_fn(move || {
+        let current = indices.as_mut()?;
+        let permutation = current.iter().map(|index| &items[*index]).collect();
+
+        // Step to the next permutation, by swapping the last ascent with the smallest larger value after it
+        match current.windows(2).rposition(|pair| pair[0] < pair[1]) {
+            Some(pivot) => {
+                let larger = current
+                    .iter()
+                    .rposition(|value| *value > current[pivot])
+                    .expect("The value after the pivot is larger");
+                current.swap(pivot, larger);
+                current[pivot + 1..].reverse();
+            }
+            None => indices = None,
+        }
+
+        Some(permutation)
+    })
+}
+
+#[test]
+fn test_pairs() {
+    let items = [1, 2, 3];
+
+    let unordered: Vec<_> = unordered_pairs(&items).collect();
+    assert_eq!(unordered, [(&1, &2), (&1, &3), (&2, &3)]);
+
+    let ordered: Vec<_> = ordered_pairs(&items).collect();
+    assert_eq!(
+        ordered,
+        [(&1, &2), (&1, &3), (&2, &1), (&2, &3), (&3, &1), (&3, &2)]
+    );
+
+    let from_iter: Vec<_> = pairs("abc".chars()).collect();
+    assert_eq!(from_iter, [('a', 'b'), ('a', 'c'), ('b', 'c')]);
+}
+
+#[test]
+fn test_combinations() {
+    let items = [1, 2, 3, 4];
+
+    let found: Vec<_> = combinations(&items, 2).collect();
+    assert_eq!(
+        found,
+        [
+            vec![&1, &2],
+            vec![&1, &3],
+            vec![&1, &4],
+            vec![&2, &3],
+            vec![&2, &4],
+            vec![&3, &4]
+        ]
+    );
+
+    assert_eq!(combinations(&items, 0).count(), 1);
+    assert_eq!(combinations(&items, 4).count(), 1);
+    assert_eq!(combinations(&items, 5).count(), 0);
+}
+
+#[test]
+fn test_permutations() {
+    let items = ['a', 'b', 'c'];
+
+    let found: Vec<String> = permutations(&items)
+        .map(|permutation| permutation.into_iter().collect())
+        .collect();
+    assert_eq!(found, ["abc", "acb", "bac", "bca", "cab", "cba"]);
+
+    assert_eq!(permutations(&[0; 5]).count(), 120);
+    assert_eq!(permutations::<u8>(&[]).count(), 1);
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use crate::parsing::{pair, unsigned};
+
 const INPUT: &str = "./src/d1/input.txt";
 
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
@@ -8,13 +10,12 @@ pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
     let mut first = Vec::with_capacity(1_000);
     let mut second = Vec::with_capacity(1_000);
 
-    for line in file.split('\n') {
-        let Some((a, b)) = line.split_once("   ") else {
-            break;
-        };
+    for line in file.lines().take_while(|line| !line.is_empty()) {
+        let ((a, b), rest) = pair(line, "   ", unsigned::<u32>)?;
 
-        let a: u32 = a.parse()?;
-        let b = b.parse()?;
+        if !rest.is_empty() {
+            return Err("Expected only two numbers on each line".into());
+        }
 
         first.push(a);
         second.push(b);
@@ -40,13 +41,12 @@ pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
     let mut first = Vec::with_capacity(1_000);
     let mut second = HashMap::with_capacity(500);
 
-    for line in file.split('\n') {
-        let Some((a, b)) = line.split_once("   ") else {
-            break;
-        };
+    for line in file.lines().take_while(|line| !line.is_empty()) {
+        let ((a, b), rest) = pair(line, "   ", unsigned::<u32>)?;
 
-        let a: u32 = a.parse()?;
-        let b: u32 = b.parse()?;
+        if !rest.is_empty() {
+            return Err("Expected only two numbers on each line".into());
+        }
 
         first.push(a);
 
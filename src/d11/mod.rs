@@ -0,0 +1,118 @@
+const INPUT: &str = "./src/d11/input.txt";
+
+use std::collections::HashMap;
+
+use crate::parsing::{separated, unsigned};
+
+/// How many stones have each engraved value, since the order of stones never matters for the count
+type Stones = HashMap<u64, u64>;
+
+fn parse_stones(input: &str) -> Result<Stones, &'static str> {
+    let (values, rest) = separated(input.trim_end(), " ", unsigned::<u64>)?;
+
+    if !rest.is_empty() {
+        return Err("Expected only stones separated by spaces");
+    }
+
+    let mut stones = Stones::new();
+    for value in values {
+        *stones.entry(value).or_default() += 1;
+    }
+
+    Ok(stones)
+}
+
+fn split_digits(value: u64) -> Option<(u64, u64)> {
+    let digits = value.checked_ilog10()? + 1;
+
+    if digits % 2 != 0 {
+        return None;
+    }
+
+    let half = 10_u64.pow(digits / 2);
+
+    Some((value / half, value % half))
+}
+
+fn blink(stones: &Stones) -> Stones {
+    let mut next = Stones::with_capacity(stones.len() * 2);
+
+    for (&value, &count) in stones {
+        if value == 0 {
+            *next.entry(1).or_default() += count;
+        } else if let Some((left, right)) = split_digits(value) {
+            *next.entry(left).or_default() += count;
+            *next.entry(right).or_default() += count;
+        } else {
+            let value = value.checked_mul(2024).expect("Stones should fit in a u64");
+            *next.entry(value).or_default() += count;
+        }
+    }
+
+    next
+}
+
+fn count_after_blinks(stones: &Stones, blinks: usize) -> u64 {
+    let mut stones = stones.clone();
+
+    for _ in 0..blinks {
+        stones = blink(&stones);
+    }
+
+    stones.values().sum()
+}
+
+#[test]
+fn test_split_digits() {
+    assert_eq!(split_digits(0), None);
+    assert_eq!(split_digits(7), None);
+    assert_eq!(split_digits(10), Some((1, 0)));
+    assert_eq!(split_digits(1000), Some((10, 0)));
+    assert_eq!(split_digits(253_000), Some((253, 0)));
+    assert_eq!(split_digits(12345), None);
+}
+
+#[test]
+fn test_single_blink() {
+    let stones = parse_stones("0 1 10 99 999").unwrap();
+
+    let expected = parse_stones("1 2024 1 0 9 9 2021976").unwrap();
+
+    assert_eq!(blink(&stones), expected);
+}
+
+#[test]
+fn test_part_one() {
+    let stones = parse_stones("125 17\n").unwrap();
+
+    assert_eq!(count_after_blinks(&stones, 6), 22);
+    assert_eq!(count_after_blinks(&stones, 25), 55312);
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let stones = parse_stones(&input)?;
+
+    println!("Found {} stones", count_after_blinks(&stones, 25));
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let stones = parse_stones("125 17").unwrap();
+
+    // Without grouping by value this would be far too many stones to simulate
+    assert_eq!(count_after_blinks(&stones, 75), 65_601_038_650_482);
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let stones = parse_stones(&input)?;
+
+    println!("Found {} stones", count_after_blinks(&stones, 75));
+
+    Ok(())
+}
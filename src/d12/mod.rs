@@ -0,0 +1,259 @@
+const INPUT: &str = "./src/d12/input.txt";
+
+use crate::{
+    grid::{Grid, GridCell},
+    parsing::ParseError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Plant(char);
+
+impl GridCell for Plant {
+    fn from_char(char: char) -> Option<Self> {
+        char.is_ascii_uppercase().then_some(Plant(char))
+    }
+
+    fn to_char(&self) -> char {
+        self.0
+    }
+}
+
+const DIRECTIONS: [(isize, isize); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Region {
+    plant: char,
+    area: usize,
+    perimeter: usize,
+    /// Every straight section of fence counts once, which is the same as the number of corners
+    sides: usize,
+}
+
+impl Region {
+    pub const fn price(&self) -> usize {
+        self.area * self.perimeter
+    }
+
+    pub const fn bulk_price(&self) -> usize {
+        self.area * self.sides
+    }
+}
+
+struct Garden {
+    plots: Vec<Vec<Plant>>,
+}
+
+impl Garden {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let grid = Grid::parse(&mut input.lines(), None)?;
+
+        Ok(Self { plots: grid.rows })
+    }
+
+    fn get(&self, (x, y): (usize, usize), (dx, dy): (isize, isize)) -> Option<Plant> {
+        let x = x.checked_add_signed(dx)?;
+        let y = y.checked_add_signed(dy)?;
+
+        self.plots.get(y)?.get(x).copied()
+    }
+
+    pub fn regions(&self) -> Vec<Region> {
+        let mut seen: Vec<Vec<bool>> = self
+            .plots
+            .iter()
+            .map(|row| vec![false; row.len()])
+            .collect();
+        let mut regions = Vec::new();
+
+        for (y, row) in self.plots.iter().enumerate() {
+            for (x, plant) in row.iter().copied().enumerate() {
+                if seen[y][x] {
+                    continue;
+                }
+                seen[y][x] = true;
+
+                let mut region = Region {
+                    plant: plant.0,
+                    area: 0,
+                    perimeter: 0,
+                    sides: 0,
+                };
+                let mut to_visit = vec![(x, y)];
+
+                while let Some(position) = to_visit.pop() {
+                    let same = |direction| self.get(position, direction) == Some(plant);
+
+                    region.area += 1;
+
+                    for direction in DIRECTIONS {
+                        if !same(direction) {
+                            region.perimeter += 1;
+                            continue;
+                        }
+
+                        let (dx, dy) = direction;
+                        let next = (
+                            position.0.wrapping_add_signed(dx),
+                            position.1.wrapping_add_signed(dy),
+                        );
+                        if !seen[next.1][next.0] {
+                            seen[next.1][next.0] = true;
+                            to_visit.push(next);
+                        }
+                    }
+
+                    // Each pair of neighbouring directions forms an outside corner if both lead out of
+                    // the region, or an inside corner if both stay in it but the diagonal between them doesn't
+                    for (first, second) in DIRECTIONS
+                        .into_iter()
+                        .zip(DIRECTIONS.into_iter().cycle().skip(1))
+                    {
+                        let diagonal = (first.0 + second.0, first.1 + second.1);
+
+                        if (!same(first) && !same(second))
+                            || (same(first) && same(second) && !same(diagonal))
+                        {
+                            region.sides += 1;
+                        }
+                    }
+                }
+
+                regions.push(region);
+            }
+        }
+
+        regions
+    }
+}
+
+fn fencing_price(input: &str) -> Result<usize, ParseError> {
+    Ok(Garden::parse(input)?
+        .regions()
+        .iter()
+        .map(Region::price)
+        .sum())
+}
+
+fn bulk_fencing_price(input: &str) -> Result<usize, ParseError> {
+    Ok(Garden::parse(input)?
+        .regions()
+        .iter()
+        .map(Region::bulk_price)
+        .sum())
+}
+
+#[test]
+fn test_regions() {
+    let input = "AAAA
+BBCD
+BBCC
+EEEC";
+
+    let regions = Garden::parse(input).unwrap().regions();
+
+    let measured: Vec<_> = regions
+        .iter()
+        .map(|region| (region.plant, region.area, region.perimeter, region.sides))
+        .collect();
+
+    assert_eq!(
+        measured,
+        [
+            ('A', 4, 10, 4),
+            ('B', 4, 8, 4),
+            ('C', 4, 10, 8),
+            ('D', 1, 4, 4),
+            ('E', 3, 8, 4)
+        ]
+    );
+}
+
+#[test]
+fn test_part_one() {
+    let small = "AAAA
+BBCD
+BBCC
+EEEC";
+    let nested = "OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO";
+    let large = "RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE";
+
+    assert_eq!(fencing_price(small), Ok(140));
+    assert_eq!(fencing_price(nested), Ok(772));
+    assert_eq!(fencing_price(large), Ok(1930));
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let price = fencing_price(&input)?;
+
+    println!("Fencing costs {price}");
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let small = "AAAA
+BBCD
+BBCC
+EEEC";
+    let nested = "OOOOO
+OXOXO
+OOOOO
+OXOXO
+OOOOO";
+    let large = "RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE";
+
+    assert_eq!(bulk_fencing_price(small), Ok(80));
+    assert_eq!(bulk_fencing_price(nested), Ok(436));
+    assert_eq!(bulk_fencing_price(large), Ok(1206));
+
+    let e_shape = "EEEEE
+EXXXX
+EEEEE
+EXXXX
+EEEEE";
+    assert_eq!(bulk_fencing_price(e_shape), Ok(236));
+
+    // The inner B regions touch only at a corner, which must not join their fences
+    let touching_corners = "AAAAAA
+AAABBA
+AAABBA
+ABBAAA
+ABBAAA
+AAAAAA";
+    assert_eq!(bulk_fencing_price(touching_corners), Ok(368));
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let price = bulk_fencing_price(&input)?;
+
+    println!("Fencing with the bulk discount costs {price}");
+
+    Ok(())
+}
@@ -1,14 +1,43 @@
+use crate::parsing::integers;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Position {
     x: u32,
     y: u32,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Machine {
     a: Position,
     b: Position,
     prize: Position,
 }
 
+// Nothing solves d13 yet, so only the tests parse machines
+#[allow(dead_code)]
+impl Machine {
+    pub fn parse(block: &str) -> Result<Self, &'static str> {
+        let mut lines = block.lines();
+        let mut next_position = |prefix: &str| {
+            let line = lines
+                .next()
+                .and_then(|line| line.strip_prefix(prefix))
+                .ok_or("Expected a button or prize line")?;
+
+            match integers(line).collect::<Vec<u32>>()[..] {
+                [x, y] => Ok(Position { x, y }),
+                _ => Err("Expected exactly an X and a Y value"),
+            }
+        };
+
+        Ok(Self {
+            a: next_position("Button A:")?,
+            b: next_position("Button B:")?,
+            prize: next_position("Prize:")?,
+        })
+    }
+}
+
 // cost = ap * 1 + bp * 3 where px = ap * ax + bp * bx & py = ap * ay + bp * by
 
 // Isolated = ap = ( px -  bp * bx ) / ax
@@ -37,3 +66,21 @@ fn test_equation() {
 
     println!("Cost for {ap} and {bp} presses was {cost}");
 }
+
+#[test]
+fn test_parse_machine() {
+    let input = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400";
+
+    assert_eq!(
+        Machine::parse(input),
+        Ok(Machine {
+            a: Position { x: 94, y: 34 },
+            b: Position { x: 22, y: 67 },
+            prize: Position { x: 8400, y: 5400 },
+        })
+    );
+
+    assert!(Machine::parse("Button A: X+94, Y+34\nPrize: X=8400, Y=5400").is_err());
+}
@@ -1,6 +1,6 @@
 const INPUT: &str = "./src/d14/input.txt";
 
-use std::iter::Peekable;
+use crate::parsing::{literal, pair, signed, unsigned, Parsed};
 
 enum Quadrant {
     One,
@@ -16,8 +16,8 @@ struct Vector {
 }
 
 impl Vector {
-    pub fn parse(input: &mut Peekable<impl Iterator<Item = char>>) -> Result<Self, &'static str> {
-        parse_set(input, parse_i8).map(|(dx, dy)| Vector { dx, dy })
+    pub fn parse(input: &str) -> Parsed<'_, Self> {
+        pair(input, ",", signed).map(|((dx, dy), rest)| (Vector { dx, dy }, rest))
     }
 }
 
@@ -68,15 +68,15 @@ impl<const LIMIT_X: u8, const LIMIT_Y: u8> Position<LIMIT_X, LIMIT_Y> {
         Position { x, y }
     }
 
-    pub fn parse(input: &mut Peekable<impl Iterator<Item = char>>) -> Result<Self, &'static str> {
-        parse_set(input, parse_u8).map(|(mut x, mut y)| {
+    pub fn parse(input: &str) -> Parsed<'_, Self> {
+        pair(input, ",", unsigned).map(|((mut x, mut y), rest)| {
             while x > LIMIT_X {
                 x -= LIMIT_X;
             }
             while y > LIMIT_Y {
                 y -= LIMIT_Y;
             }
-            Position { x, y }
+            (Position { x, y }, rest)
         })
     }
 
@@ -107,90 +107,17 @@ struct Robot<const LIMIT_X: u8, const LIMIT_Y: u8> {
     direction: Vector,
 }
 
-fn parse_u8(iter: &mut Peekable<impl Iterator<Item = char>>) -> Result<u8, &'static str> {
-    let first = match iter.peek().copied().ok_or("Found no numbers in string")? {
-        v @ '0'..='9' => v as u8 - b'0',
-        _ => return Err("Expected input to be between 0..=9"),
-    };
-    iter.next();
-
-    let second = match iter.peek().copied() {
-        Some(v @ '0'..='9') => v as u8 - b'0',
-        _ => return Ok(first),
-    };
-    iter.next();
-
-    let value = first * 10 + second;
-
-    let third = match iter.peek().copied() {
-        Some(v @ '0'..='9') => v as u8 - b'0',
-        _ => return Ok(first),
-    };
-    iter.next();
-
-    value
-        .checked_mul(10)
-        .and_then(|value| value.checked_add(third))
-        .ok_or("Expected only values 0..=255")
-}
-
-fn parse_i8(iter: &mut Peekable<impl Iterator<Item = char>>) -> Result<i8, &'static str> {
-    let negative = if iter.peek().ok_or("Expected string to be longer than 0")? == &'-' {
-        iter.next();
-        true
-    } else {
-        false
-    };
-
-    let number: i16 = match parse_u8(iter) {
-        Ok(v) => v.into(),
-        Err("Expected only values 0..=255") => return Err("Expected only values -128..=127"),
-        Err(e) => return Err(e),
-    };
-
-    let number = if negative { -number } else { number };
-
-    number.try_into().map_err(|_| "Expected values -128..=127")
-}
-
-fn parse_set<Int, I: Iterator<Item = char>>(
-    iter: &mut Peekable<I>,
-    parser: impl Fn(&mut Peekable<I>) -> Result<Int, &'static str>,
-) -> Result<(Int, Int), &'static str> {
-    let first = parser(iter)?;
-
-    if iter.peek().copied() != Some(',') {
-        return Err("Expected separator");
-    }
-    iter.next();
-
-    let second = parser(iter)?;
-
-    Ok((first, second))
-}
-
 impl<const LIMIT_X: u8, const LIMIT_Y: u8> Robot<LIMIT_X, LIMIT_Y> {
     pub fn try_parse(input: &str) -> Result<Self, &'static str> {
-        let mut input = input.chars().peekable();
-
-        if input.next() != Some('p') {
-            return Err("Expected bot description to start with p");
-        }
+        let input = literal(input, "p=")?;
+        let (start, input) = Position::parse(input)?;
+        let input = literal(input, " v=")?;
+        let (vector, input) = Vector::parse(input)?;
 
-        if input.next() != Some('=') {
-            return Err("Expected p to be followed by =");
+        if !input.is_empty() {
+            return Err("Expected nothing after the velocity");
         }
 
-        let start = Position::parse(&mut input)?;
-
-        for char in [' ', 'v', '='] {
-            if input.next() != Some(char) {
-                return Err("Expected ' v=' after position");
-            }
-        }
-
-        let vector = Vector::parse(&mut input)?;
-
         Ok(Self {
             starting: start,
             direction: vector,
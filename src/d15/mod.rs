@@ -1,3 +1,10 @@
+const INPUT: &str = "./src/d15/input.txt";
+
+use crate::{
+    grid::{Grid, GridCell},
+    parsing::ParseError,
+};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CellContent {
     None,
@@ -6,18 +13,32 @@ enum CellContent {
     Robot,
 }
 
+impl GridCell for CellContent {
+    const MARKERS: &'static [char] = &['@'];
+
+    fn from_char(char: char) -> Option<Self> {
+        Some(match char {
+            '.' => CellContent::None,
+            '#' => CellContent::Wall,
+            'O' => CellContent::Box,
+            '@' => CellContent::Robot,
+            _ => return None,
+        })
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            CellContent::None => '.',
+            CellContent::Wall => '#',
+            CellContent::Box => 'O',
+            CellContent::Robot => '@',
+        }
+    }
+}
+
 impl std::fmt::Display for CellContent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                CellContent::None => ' ',
-                CellContent::Wall => '#',
-                CellContent::Box => 'O',
-                CellContent::Robot => 'R',
-            }
-        )
+        write!(f, "{}", self.to_char())
     }
 }
 
@@ -100,9 +121,7 @@ impl<const LIMIT_X: usize, const LIMIT_Y: usize> Position<LIMIT_X, LIMIT_Y> {
     }
 
     pub fn value(self) -> u64 {
-        let x = u64::from(self.x) + 1;
-        let y = u64::from(self.y) + 1;
-        x + 100 * y
+        u64::from(self.x) + 100 * u64::from(self.y)
     }
 }
 
@@ -129,9 +148,59 @@ impl<I: TryInto<u8>, const LIMIT_X: usize, const LIMIT_Y: usize> TryFrom<(I, I)>
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MoveOutcome {
+    Moved,
+    Pushed { boxes: usize },
+    Blocked,
+}
+
+impl std::fmt::Display for MoveOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoveOutcome::Moved => write!(f, "moved"),
+            MoveOutcome::Pushed { boxes: 1 } => write!(f, "pushed 1 box"),
+            MoveOutcome::Pushed { boxes } => write!(f, "pushed {boxes} boxes"),
+            MoveOutcome::Blocked => write!(f, "blocked"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CellChange<const WIDTH: usize, const HEIGHT: usize> {
+    position: Position<WIDTH, HEIGHT>,
+    before: CellContent,
+    after: CellContent,
+}
+
+/// A single robot move, with every cell it changed so it can be reverted
+#[derive(Debug, Clone)]
+struct MoveRecord<const WIDTH: usize, const HEIGHT: usize> {
+    direction: Direction,
+    outcome: MoveOutcome,
+    changes: Vec<CellChange<WIDTH, HEIGHT>>,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> std::fmt::Display for MoveRecord<WIDTH, HEIGHT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.direction, self.outcome)?;
+        for (change, index) in self.changes.iter().zip(0..) {
+            let separator = if index == 0 { ':' } else { ',' };
+            write!(
+                f,
+                "{separator} {} '{}' -> '{}'",
+                change.position, change.before, change.after
+            )?;
+        }
+        Ok(())
+    }
+}
+
 struct Board<const WIDTH: usize, const HEIGHT: usize> {
     content: [[CellContent; WIDTH]; HEIGHT],
     robot: Position<WIDTH, HEIGHT>,
+    history: Vec<MoveRecord<WIDTH, HEIGHT>>,
+    undone: Vec<MoveRecord<WIDTH, HEIGHT>>,
 }
 
 impl<const WIDTH: usize, const HEIGHT: usize> std::ops::Index<Position<WIDTH, HEIGHT>>
@@ -153,86 +222,152 @@ impl<const WIDTH: usize, const HEIGHT: usize> std::ops::IndexMut<Position<WIDTH,
 }
 
 impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
-    pub fn parse(
-        lines: &mut impl Iterator<Item = impl Iterator<Item = char>>,
-    ) -> Result<Self, String> {
-        // First line is discardable
-        lines.next();
-
-        let mut robot: Option<Position<WIDTH, HEIGHT>> = None;
-        let mut content = [[CellContent::None; WIDTH]; HEIGHT];
-
-        for line in content
-            .iter_mut()
-            .enumerate()
-            .map(|row| lines.next().map(|line| (line, row)))
-        {
-            let Some((line, (y, row))) = line else {
-                return Err(format!("Expected {HEIGHT} lines of content"));
-            };
-
-            let mut line = line.peekable();
-
-            if line.next() != Some('#') {
-                return Err("Expected all map lines to start with #".to_owned());
-            }
+    /// Parses the map from the first `HEIGHT` lines, leaving the rest of the lines in the iterator
+    pub fn parse<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<Self, ParseError> {
+        let grid = Grid::<CellContent>::parse(lines, Some((WIDTH, HEIGHT)))?;
+
+        // Moves don't check the edges of the map, so the robot relies on walls all around it
+        let broken_border = (0..HEIGHT)
+            .flat_map(|y| (0..WIDTH).map(move |x| (x, y)))
+            .filter(|&(x, y)| y == 0 || y == HEIGHT - 1 || x == 0 || x == WIDTH - 1)
+            .find(|&(x, y)| grid.rows[y][x] != CellContent::Wall);
+
+        if let Some((x, y)) = broken_border {
+            return Err(grid.error_at(
+                (x, y),
+                format!(
+                    "Expected the map border to be walls, found {}",
+                    grid.rows[y][x]
+                ),
+            ));
+        }
 
-            for cell in row
-                .iter_mut()
-                .enumerate()
-                .map(|map_cell| line.next().map(|char| (char, map_cell)))
-            {
-                let Some((cell, (x, map_cell))) = cell else {
-                    return Err(format!("Expected {WIDTH} cells in the map"));
-                };
-
-                *map_cell = match cell {
-                    '.' => CellContent::None,
-                    'O' => CellContent::Box,
-                    '@' => {
-                        if let Some(existing) = robot {
-                            return Err(format!("Expected only one robot, but found one at {existing:?} and ({x}, {y})"));
-                        }
-
-                        robot = Some(Position::new(x as u8, y as u8));
-
-                        CellContent::Robot
-                    }
-                    '#' => CellContent::Wall,
-                    cell => return Err(format!("Found unexpected cell value {cell}")),
-                }
-            }
+        let robot = grid.marker('@').expect("The robot is a marker");
+        let robot = Position::try_from(robot)
+            .map_err(|()| grid.error_at(robot, "Robot does not fit in a position"))?;
+
+        let content = grid
+            .into_array()
+            .expect("The grid was parsed with the size of the board");
+
+        Ok(Self {
+            content,
+            robot,
+            history: Vec::new(),
+            undone: Vec::new(),
+        })
+    }
+
+    pub fn move_robot(&mut self, direction: Direction) -> MoveOutcome {
+        // A new move invalidates anything we could have redone
+        self.undone.clear();
+
+        let record = self.plan_move(direction);
+        self.apply_changes(
+            record
+                .changes
+                .iter()
+                .map(|change| (change.position, change.after)),
+        );
+
+        if let Err(robots) = self.verify_robots() {
+            panic!("After move {record} expected only one robot, found multiple robots {robots:?}")
         }
 
-        let Some(robot) = robot else {
-            return Err("Did not find a robot in map".to_owned());
-        };
+        let outcome = record.outcome;
+        self.history.push(record);
+        outcome
+    }
 
-        Ok(Self { content, robot })
+    pub fn undo(&mut self) -> Option<&MoveRecord<WIDTH, HEIGHT>> {
+        let record = self.history.pop()?;
+        self.apply_changes(
+            record
+                .changes
+                .iter()
+                .rev()
+                .map(|change| (change.position, change.before)),
+        );
+        self.undone.push(record);
+        self.undone.last()
     }
 
-    pub fn move_robot(&mut self, direction: Direction) {
+    pub fn redo(&mut self) -> Option<&MoveRecord<WIDTH, HEIGHT>> {
+        let record = self.undone.pop()?;
+        self.apply_changes(
+            record
+                .changes
+                .iter()
+                .map(|change| (change.position, change.after)),
+        );
+        self.history.push(record);
+        self.history.last()
+    }
+
+    pub fn history(&self) -> &[MoveRecord<WIDTH, HEIGHT>] {
+        &self.history
+    }
+
+    fn apply_changes(
+        &mut self,
+        changes: impl Iterator<Item = (Position<WIDTH, HEIGHT>, CellContent)>,
+    ) {
+        for (position, cell) in changes {
+            self[position] = cell;
+            if cell == CellContent::Robot {
+                self.robot = position;
+            }
+        }
+    }
+
+    fn plan_move(&self, direction: Direction) -> MoveRecord<WIDTH, HEIGHT> {
         if let Err(robots) = self.verify_robots() {
             panic!("Pre move sanity check failed with {robots:?}")
         }
 
+        let blocked = MoveRecord {
+            direction,
+            outcome: MoveOutcome::Blocked,
+            changes: Vec::new(),
+        };
+
         let start_robot_position = self.robot;
         // We look from the current robot position in direction until we find an open space
         let Some(direct_neighbor) = self.robot.neighbor(direction) else {
-            return;
+            return blocked;
+        };
+
+        let leave_start = CellChange {
+            position: start_robot_position,
+            before: CellContent::Robot,
+            after: CellContent::None,
         };
 
-        if self[direct_neighbor] == CellContent::None {
-            self.robot = direct_neighbor;
-            self[direct_neighbor] = CellContent::Robot;
-            self[start_robot_position] = CellContent::None;
-            return;
+        match self[direct_neighbor] {
+            CellContent::None => {
+                return MoveRecord {
+                    direction,
+                    outcome: MoveOutcome::Moved,
+                    changes: vec![
+                        leave_start,
+                        CellChange {
+                            position: direct_neighbor,
+                            before: CellContent::None,
+                            after: CellContent::Robot,
+                        },
+                    ],
+                }
+            }
+            // The robot can't move into a wall, and should never look past it
+            CellContent::Wall => return blocked,
+            CellContent::Box | CellContent::Robot => (),
         }
 
-        let Some(first_empty) = direct_neighbor
+        let Some((first_empty, boxes)) = direct_neighbor
             .neighbors(direction)
-            .find_map(|position| match self[position] {
-                CellContent::None => Some(Some(position)),
+            .zip(1..)
+            .find_map(|(position, boxes)| match self[position] {
+                CellContent::None => Some(Some((position, boxes))),
                 // If we encounter a wall, we have to stop the search since it can't be moved
                 CellContent::Wall => Some(None),
                 CellContent::Robot => unreachable!("Search should always happen from the robot, and therefore never encounter the robot again, started at {start_robot_position} and looking at {position}"),
@@ -240,17 +375,26 @@ impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
             })
             .flatten()
         else {
-            return;
+            return blocked;
         };
 
         // Now we can mark first_empty as a box, move the robot into direct_neighbor, and clear the current robot position
-        self[first_empty] = CellContent::Box;
-        self[direct_neighbor] = CellContent::Robot;
-        self[start_robot_position] = CellContent::None;
-        self.robot = direct_neighbor;
-
-        if let Err(robots) = self.verify_robots() {
-            panic!("After move from {start_robot_position} expected only {direct_neighbor}, found multiple robots {robots:?}")
+        MoveRecord {
+            direction,
+            outcome: MoveOutcome::Pushed { boxes },
+            changes: vec![
+                leave_start,
+                CellChange {
+                    position: direct_neighbor,
+                    before: CellContent::Box,
+                    after: CellContent::Robot,
+                },
+                CellChange {
+                    position: first_empty,
+                    before: CellContent::None,
+                    after: CellContent::Box,
+                },
+            ],
         }
     }
 
@@ -343,15 +487,14 @@ fn test_part_one_small() {
 
 <^^>>>vv<v>>v<<";
 
-    let mut input = input.lines().map(|line| line.chars());
+    let mut input = input.lines();
 
-    let mut board = Board::<6, 6>::parse(&mut input).unwrap();
+    let mut board = Board::<8, 8>::parse(&mut input).unwrap();
 
-    // We then discard the bottom wall and spacer
-    input.next();
+    // We then discard the spacer
     input.next();
 
-    for input in input.flatten().filter_map(Direction::parse) {
+    for input in input.flat_map(str::chars).filter_map(Direction::parse) {
         board.move_robot(input);
     }
 
@@ -384,20 +527,172 @@ vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
 ^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
 v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
 
-    let mut input = input.lines().map(|line| line.chars());
+    let mut input = input.lines();
 
-    let mut board = Board::<8, 8>::parse(&mut input).unwrap();
+    let mut board = Board::<10, 10>::parse(&mut input).unwrap();
 
-    // We then discard the bottom wall and spacer
-    input.next();
+    // We then discard the spacer
     input.next();
 
-    for input in input.flatten().filter_map(Direction::parse) {
+    for input in input.flat_map(str::chars).filter_map(Direction::parse) {
         board.move_robot(input);
-        println!("After {input} board is now\n{board}")
     }
 
     let sum = board.box_sum();
 
     assert_eq!(sum, 10092);
 }
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let mut input = input.lines();
+
+    let mut board = Board::<50, 50>::parse(&mut input)?;
+
+    // We then discard the spacer
+    input.next();
+
+    for input in input.flat_map(str::chars).filter_map(Direction::parse) {
+        board.move_robot(input);
+    }
+
+    let blocked = board
+        .history()
+        .iter()
+        .filter(|record| record.outcome == MoveOutcome::Blocked)
+        .count();
+
+    println!(
+        "Robot made {} moves, {blocked} of them blocked",
+        board.history().len()
+    );
+    println!("Sum is {}", board.box_sum());
+
+    Ok(())
+}
+
+// Steps back through the last `steps` moves and forward again, printing what each one changed
+pub fn replay(steps: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let mut input = input.lines();
+
+    let mut board = Board::<50, 50>::parse(&mut input)?;
+
+    // We then discard the spacer
+    input.next();
+
+    for input in input.flat_map(str::chars).filter_map(Direction::parse) {
+        board.move_robot(input);
+    }
+
+    for _ in 0..steps {
+        let Some(record) = board.undo() else {
+            break;
+        };
+        println!("Undid {record}");
+    }
+
+    print!("{board}");
+
+    while let Some(record) = board.redo() {
+        println!("Redid {record}");
+    }
+
+    println!("Sum is {}", board.box_sum());
+
+    Ok(())
+}
+
+#[test]
+fn test_parse_requires_border() {
+    let input = "#####
+#.@.#
+#.O..
+#...#
+#####";
+
+    let mut input = input.lines();
+
+    let error = Board::<5, 5>::parse(&mut input).err().unwrap();
+    assert_eq!((error.line, error.column), (3, 5));
+    assert_eq!(error.snippet, "#.O..");
+}
+
+#[test]
+fn test_parse_error_location() {
+    let parse = |input: &str| Board::<5, 4>::parse(&mut input.lines()).err().unwrap();
+
+    let error = parse("#####\n#.@x#\n#...#\n#####");
+    assert_eq!(
+        error.to_string(),
+        "line 2, column 4: Found unexpected cell value x\n2 | #.@x#\n  |    ^"
+    );
+
+    let error = parse("#####\n#.@.#\n#.@.#\n#####");
+    assert_eq!((error.line, error.column), (3, 3));
+
+    let error = parse("#####\n#.@.##\n#...#\n#####");
+    assert_eq!((error.line, error.column), (2, 6));
+
+    let error = parse("#####\n#.@.#");
+    assert_eq!((error.line, error.column), (2, 6));
+}
+
+#[test]
+fn test_undo_redo() {
+    let input = "########
+#..O.O.#
+##@.O..#
+#...O..#
+#.#.O..#
+#...O..#
+#......#
+########
+
+<^^>>>vv<v>>v<<";
+
+    let mut input = input.lines();
+
+    let mut board = Board::<8, 8>::parse(&mut input).unwrap();
+    let initial = board.to_string();
+
+    input.next();
+
+    let outcomes: Vec<_> = input
+        .flat_map(str::chars)
+        .filter_map(Direction::parse)
+        .map(|direction| board.move_robot(direction))
+        .collect();
+
+    assert_eq!(outcomes[0], MoveOutcome::Blocked);
+    assert_eq!(outcomes[1], MoveOutcome::Moved);
+    assert_eq!(outcomes[3], MoveOutcome::Pushed { boxes: 1 });
+    assert_eq!(outcomes[4], MoveOutcome::Pushed { boxes: 2 });
+    assert_eq!(outcomes[5], MoveOutcome::Blocked);
+    assert_eq!(board.history().len(), outcomes.len());
+
+    let last = board.to_string();
+
+    while board.undo().is_some() {}
+    assert_eq!(board.to_string(), initial);
+
+    while board.redo().is_some() {}
+    assert_eq!(board.to_string(), last);
+    assert_eq!(board.box_sum(), 2028);
+}
+
+#[test]
+fn test_board_round_trip() {
+    let map = "######
+#.O..#
+#.@O.#
+#....#
+######
+";
+
+    let board = Board::<6, 5>::parse(&mut map.lines()).unwrap();
+
+    assert_eq!(board.to_string(), map);
+}
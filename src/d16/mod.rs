@@ -1,29 +1,53 @@
-use std::num::NonZeroU32;
+const INPUT: &str = "./src/d16/input.txt";
+
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use crate::{
+    grid::{Grid, GridCell},
+    parsing::ParseError,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CellContent {
-    None { lowest_cost: Option<NonZeroU32> },
+    None,
     Wall,
     Start,
     End,
 }
 
+impl GridCell for CellContent {
+    const MARKERS: &'static [char] = &['S', 'E'];
+
+    fn from_char(char: char) -> Option<Self> {
+        Some(match char {
+            '.' => CellContent::None,
+            '#' => CellContent::Wall,
+            'S' => CellContent::Start,
+            'E' => CellContent::End,
+            _ => return None,
+        })
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            CellContent::None => '.',
+            CellContent::Wall => '#',
+            CellContent::Start => 'S',
+            CellContent::End => 'E',
+        }
+    }
+}
+
 impl std::fmt::Display for CellContent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                CellContent::None { .. } => ' ',
-                CellContent::Wall => '#',
-                CellContent::Start => 'S',
-                CellContent::End => 'E',
-            }
-        )
+        write!(f, "{}", self.to_char())
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 enum Direction {
     Up,
     Right,
@@ -32,6 +56,13 @@ enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
     pub const fn vector(self) -> (i8, i8) {
         match self {
             Direction::Up => (0, -1),
@@ -50,6 +81,15 @@ impl Direction {
         }
     }
 
+    pub const fn reverse(self) -> Self {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+
     pub const fn turn_anti_clockwise(self) -> Self {
         match self {
             Direction::Up => Direction::Left,
@@ -75,20 +115,13 @@ impl std::fmt::Display for Direction {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 struct Position<const LIMIT_X: usize, const LIMIT_Y: usize> {
     x: u8,
     y: u8,
 }
 
 impl<const LIMIT_X: usize, const LIMIT_Y: usize> Position<LIMIT_X, LIMIT_Y> {
-    #[must_use]
-    pub const fn new(x: u8, y: u8) -> Self {
-        assert!((x as usize) < LIMIT_X);
-        assert!((y as usize) < LIMIT_Y);
-        Self { x, y }
-    }
-
     pub fn neighbor(self, direction: Direction) -> Option<Self> {
         let vector = direction.vector();
         let x = self.x.checked_add_signed(vector.0)?;
@@ -96,26 +129,48 @@ impl<const LIMIT_X: usize, const LIMIT_Y: usize> Position<LIMIT_X, LIMIT_Y> {
 
         Position::try_from((x, y)).ok()
     }
+}
 
-    pub fn neighbors_with_costs(
-        self,
+#[derive(Debug, Clone)]
+struct CostModel<const WIDTH: usize, const HEIGHT: usize> {
+    step: u32,
+    turn: u32,
+    /// The cost of turning around in place, or `None` if it has to be done as two turns
+    u_turn: Option<u32>,
+    tile_weights: HashMap<Position<WIDTH, HEIGHT>, u32>,
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> Default for CostModel<WIDTH, HEIGHT> {
+    fn default() -> Self {
+        Self {
+            step: 1,
+            turn: 1000,
+            u_turn: None,
+            tile_weights: HashMap::new(),
+        }
+    }
+}
+
+impl<const WIDTH: usize, const HEIGHT: usize> CostModel<WIDTH, HEIGHT> {
+    pub fn moves(
+        &self,
+        position: Position<WIDTH, HEIGHT>,
         direction: Direction,
-    ) -> impl Iterator<Item = (NonZeroU32, Self, Direction)> {
-        let one = NonZeroU32::new(1).unwrap();
-        let straight = self
-            .neighbor(direction)
-            .map(|position| (one, position, direction));
-
-        let thousand_and_one = NonZeroU32::new(1001).unwrap();
-        let clockwise = self
-            .neighbor(direction.turn_clockwise())
-            .map(|position| (thousand_and_one, position, direction.turn_clockwise()));
-
-        let anti_clockwise = self
-            .neighbor(direction.turn_anti_clockwise())
-            .map(|position| (thousand_and_one, position, direction.turn_anti_clockwise()));
-
-        [straight, clockwise, anti_clockwise].into_iter().flatten()
+    ) -> impl Iterator<Item = (u32, Position<WIDTH, HEIGHT>, Direction)> {
+        let straight = position.neighbor(direction).map(|position| {
+            let weight = self.tile_weights.get(&position).copied().unwrap_or(0);
+            (self.step + weight, position, direction)
+        });
+
+        let clockwise = Some((self.turn, position, direction.turn_clockwise()));
+        let anti_clockwise = Some((self.turn, position, direction.turn_anti_clockwise()));
+        let reverse = self
+            .u_turn
+            .map(|cost| (cost, position, direction.reverse()));
+
+        [straight, clockwise, anti_clockwise, reverse]
+            .into_iter()
+            .flatten()
     }
 }
 
@@ -158,141 +213,392 @@ impl<const WIDTH: usize, const HEIGHT: usize> std::ops::Index<Position<WIDTH, HE
     }
 }
 
-impl<const WIDTH: usize, const HEIGHT: usize> std::ops::IndexMut<Position<WIDTH, HEIGHT>>
-    for Board<WIDTH, HEIGHT>
-{
-    fn index_mut(&mut self, index: Position<WIDTH, HEIGHT>) -> &mut Self::Output {
-        &mut self.content[index.y as usize][index.x as usize]
+impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
+    pub fn parse<'a>(lines: &mut impl Iterator<Item = &'a str>) -> Result<Self, ParseError> {
+        let grid = Grid::<CellContent>::parse(lines, Some((WIDTH, HEIGHT)))?;
+
+        let marker = |char| {
+            let found = grid.marker(char).expect("Start and end are markers");
+            Position::try_from(found)
+                .map_err(|()| grid.error_at(found, format!("{char} does not fit in a position")))
+        };
+
+        let start = marker('S')?;
+        let end = marker('E')?;
+
+        Ok(Board {
+            content: grid
+                .into_array()
+                .expect("The grid was parsed with the size of the board"),
+            start,
+            end,
+        })
     }
-}
 
-impl<const WIDTH: usize, const HEIGHT: usize> Board<WIDTH, HEIGHT> {
-    pub fn parse(
-        lines: &mut impl Iterator<Item = impl Iterator<Item = char>>,
-    ) -> Result<Self, String> {
-        // First line is discardable
-        lines.next();
-
-        let mut start: Option<Position<WIDTH, HEIGHT>> = None;
-        let mut end: Option<Position<WIDTH, HEIGHT>> = None;
-        let mut content = [[CellContent::None { lowest_cost: None }; WIDTH]; HEIGHT];
-
-        for line in content
-            .iter_mut()
-            .zip(0..)
-            .map(|row| lines.next().map(|line| (line, row)))
-        {
-            let Some((line, (row, y))) = line else {
-                return Err(format!("Expected {HEIGHT} lines of content"));
-            };
-
-            let mut line = line.peekable();
-
-            if line.next() != Some('#') {
-                return Err("Expected all map lines to start with #".to_owned());
-            }
+    pub fn search_from(
+        &self,
+        start: Position<WIDTH, HEIGHT>,
+        direction: Direction,
+        costs: &CostModel<WIDTH, HEIGHT>,
+    ) -> PathSearch<WIDTH, HEIGHT> {
+        PathSearch::run(self, start, direction, costs)
+    }
 
-            for cell in row
-                .iter_mut()
-                .zip(0..)
-                .map(|map_cell| line.next().map(|char| (char, map_cell)))
-            {
-                let Some((cell, (map_cell, x))) = cell else {
-                    return Err(format!("Expected {WIDTH} cells in the map"));
-                };
-
-                *map_cell = match cell {
-                    '.' => CellContent::None { lowest_cost: None },
-                    '#' => CellContent::Wall,
-                    'S' => {
-                        let found = Position { x, y };
-                        if let Some(existing) = start {
-                            return Err(format!("Found two starts, {existing} and {found}"));
-                        }
-
-                        start = Some(found);
-                        CellContent::Start
-                    }
-                    'E' => {
-                        let found = Position { x, y };
-                        if let Some(existing) = end {
-                            return Err(format!("Found two ends, {existing} and {found}"));
-                        }
-
-                        end = Some(found);
-                        CellContent::End
-                    }
-                    cell => return Err(format!("Found unexpected cell value {cell}")),
+    pub fn find_cheapest_path(&self) -> u64 {
+        self.search_from(self.start, Direction::Right, &CostModel::default())
+            .cheapest_cost(self.end)
+            .expect("Expected to find at least one path")
+            .into()
+    }
+
+    pub fn find_best_tiles(&self) -> HashSet<Position<WIDTH, HEIGHT>> {
+        self.search_from(self.start, Direction::Right, &CostModel::default())
+            .best_tiles(self.end)
+    }
+
+    pub fn render_tiles(&self, tiles: &HashSet<Position<WIDTH, HEIGHT>>) -> String {
+        let mut output = String::with_capacity((WIDTH + 1) * HEIGHT);
+
+        for (row, y) in self.content.iter().zip(0..) {
+            for (cell, x) in row.iter().zip(0..) {
+                if tiles.contains(&Position { x, y }) {
+                    output.push('O');
+                } else {
+                    output.push(cell.to_char());
                 }
             }
+            output.push('\n');
         }
 
-        match (start, end) {
-            (Some(start), Some(end)) => Ok(Board {
-                content,
-                start,
-                end,
-            }),
-            (None, _) => Err("Failed to find start".to_owned()),
-            (_, None) => Err("Failed to find end".to_owned()),
-        }
+        output
     }
+}
 
-    pub fn find_cheapest_path(&mut self) -> u64 {
-        struct PathCandidate<const WIDTH: usize, const HEIGHT: usize> {
-            running_cost: NonZeroU32,
-            position: Position<WIDTH, HEIGHT>,
-            direction: Direction,
+impl<const WIDTH: usize, const HEIGHT: usize> std::fmt::Display for Board<WIDTH, HEIGHT> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.content {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
         }
+        Ok(())
+    }
+}
 
-        let mut cheapest = None;
-        let mut to_check: Vec<_> = self
-            .start
-            .neighbors_with_costs(Direction::Right)
-            .map(|(running_cost, position, direction)| PathCandidate {
-                running_cost,
-                position,
-                direction,
-            })
-            .collect();
+type State<const WIDTH: usize, const HEIGHT: usize> = (Position<WIDTH, HEIGHT>, Direction);
+
+struct PathSearch<const WIDTH: usize, const HEIGHT: usize> {
+    lowest_costs: HashMap<State<WIDTH, HEIGHT>, u32>,
+    /// Every state that reaches a given state at its lowest cost
+    predecessors: HashMap<State<WIDTH, HEIGHT>, Vec<State<WIDTH, HEIGHT>>>,
+}
 
-        while let Some(run) = to_check.pop() {
-            for (added_cost, position, direction) in
-                run.position.neighbors_with_costs(run.direction)
+impl<const WIDTH: usize, const HEIGHT: usize> PathSearch<WIDTH, HEIGHT> {
+    // Dijkstra over (position, direction), as turning costs depend on which way we face
+    fn run(
+        board: &Board<WIDTH, HEIGHT>,
+        start: Position<WIDTH, HEIGHT>,
+        direction: Direction,
+        costs: &CostModel<WIDTH, HEIGHT>,
+    ) -> Self {
+        let mut lowest_costs = HashMap::new();
+        let mut predecessors: HashMap<_, Vec<_>> = HashMap::new();
+        let mut to_check = BinaryHeap::new();
+
+        lowest_costs.insert((start, direction), 0_u32);
+        to_check.push(Reverse((0, start, direction)));
+
+        while let Some(Reverse((running_cost, position, direction))) = to_check.pop() {
+            if lowest_costs
+                .get(&(position, direction))
+                .is_some_and(|lowest| *lowest < running_cost)
             {
-                let running_cost = run
-                    .running_cost
-                    .checked_add(added_cost.into())
-                    .expect("All values to fit in cost");
+                // We've already handled this state with a cheaper path
+                continue;
+            }
 
-                if position == self.end {
+            let from = (position, direction);
+
+            for (added_cost, position, direction) in costs.moves(position, direction) {
+                if board[position] == CellContent::Wall {
                     continue;
                 }
 
-                // We check our current tile if we're on a more optimal path
-                match &mut self[position] {
-                    CellContent::None { lowest_cost } => {
-                        if lowest_cost.is_some_and(|value| value < running_cost) {
-                            // Then we've already found a better path to this tile, give up
-                            continue;
-                        }
-                        *lowest_cost = Some(running_cost);
-                    }
-                    CellContent::Wall | CellContent::Start => continue,
-                    CellContent::End => {
-                        cheapest = Some(cheapest.unwrap_or(running_cost).min(running_cost));
+                let running_cost = running_cost
+                    .checked_add(added_cost)
+                    .expect("All values to fit in cost");
+                let state = (position, direction);
+
+                match lowest_costs
+                    .get(&state)
+                    .map(|lowest| running_cost.cmp(lowest))
+                {
+                    Some(Ordering::Greater) => continue,
+                    Some(Ordering::Equal) => {
+                        // Another path of the same cost, we keep it so we can find every best path
+                        predecessors.entry(state).or_default().push(from);
                         continue;
                     }
+                    Some(Ordering::Less) | None => (),
                 }
 
-                to_check.push(PathCandidate {
-                    running_cost,
-                    position,
-                    direction,
-                });
+                lowest_costs.insert(state, running_cost);
+                predecessors.insert(state, vec![from]);
+                to_check.push(Reverse((running_cost, position, direction)));
+            }
+        }
+
+        Self {
+            lowest_costs,
+            predecessors,
+        }
+    }
+
+    pub fn cheapest_cost(&self, to: Position<WIDTH, HEIGHT>) -> Option<u32> {
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| self.lowest_costs.get(&(to, direction)).copied())
+            .min()
+    }
+
+    pub fn best_tiles(&self, to: Position<WIDTH, HEIGHT>) -> HashSet<Position<WIDTH, HEIGHT>> {
+        let Some(cost) = self.cheapest_cost(to) else {
+            return HashSet::new();
+        };
+
+        let mut seen = HashSet::new();
+        // We may reach the end from several directions at the same cost
+        let mut to_visit: Vec<_> = Direction::ALL
+            .into_iter()
+            .map(|direction| (to, direction))
+            .filter(|state| self.lowest_costs.get(state) == Some(&cost))
+            .collect();
+
+        while let Some(state) = to_visit.pop() {
+            if !seen.insert(state) {
+                continue;
+            }
+
+            if let Some(previous) = self.predecessors.get(&state) {
+                to_visit.extend(previous.iter().copied());
             }
         }
 
-        cheapest.expect("Expected to find at least one path")
+        seen.into_iter().map(|(position, _)| position).collect()
     }
 }
+
+#[test]
+fn test_part_one_small() {
+    let input = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    let board = Board::<15, 15>::parse(&mut input.lines()).unwrap();
+
+    assert_eq!(board.find_cheapest_path(), 7036);
+}
+
+#[test]
+fn test_part_one_big() {
+    let input = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+
+    let board = Board::<17, 17>::parse(&mut input.lines()).unwrap();
+
+    assert_eq!(board.find_cheapest_path(), 11048);
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let board = Board::<141, 141>::parse(&mut input.lines())?;
+
+    let cost = board.find_cheapest_path();
+
+    println!("Cheapest path costs {cost}");
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two_small() {
+    let input = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    let expected = "###############
+#.......#....O#
+#.#.###.#.###O#
+#.....#.#...#O#
+#.###.#####.#O#
+#.#.#.......#O#
+#.#.#####.###O#
+#..OOOOOOOOO#O#
+###O#O#####O#O#
+#OOO#O....#O#O#
+#O#O#O###.#O#O#
+#OOOOO#...#O#O#
+#O###.#.#.#O#O#
+#O..#.....#OOO#
+###############
+";
+
+    let board = Board::<15, 15>::parse(&mut input.lines()).unwrap();
+
+    let tiles = board.find_best_tiles();
+
+    assert_eq!(tiles.len(), 45);
+    assert_eq!(board.render_tiles(&tiles), expected);
+    assert_eq!(board.to_string(), format!("{input}\n"));
+}
+
+#[test]
+fn test_part_two_big() {
+    let input = "#################
+#...#...#...#..E#
+#.#.#.#.#.#.#.#.#
+#.#.#.#...#...#.#
+#.#.#.#.###.#.#.#
+#...#.#.#.....#.#
+#.#.#.#.#.#####.#
+#.#...#.#.#.....#
+#.#.#####.#.###.#
+#.#.#.......#...#
+#.#.###.#####.###
+#.#.#...#.....#.#
+#.#.#.#####.###.#
+#.#.#.........#.#
+#.#.#.#########.#
+#S#.............#
+#################";
+
+    let board = Board::<17, 17>::parse(&mut input.lines()).unwrap();
+
+    assert_eq!(board.find_best_tiles().len(), 64);
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let board = Board::<141, 141>::parse(&mut input.lines())?;
+
+    let tiles = board.find_best_tiles();
+
+    println!("{}", board.render_tiles(&tiles));
+    println!("Found {} tiles on the best paths", tiles.len());
+
+    Ok(())
+}
+
+#[test]
+fn test_repeated_searches() {
+    let input = "###############
+#.......#....E#
+#.#.###.#.###.#
+#.....#.#...#.#
+#.###.#####.#.#
+#.#.#.......#.#
+#.#.#####.###.#
+#...........#.#
+###.#.#####.#.#
+#...#.....#.#.#
+#.#.#.###.#.#.#
+#.....#...#.#.#
+#.###.#.#.#.#.#
+#S..#.....#...#
+###############";
+
+    let board = Board::<15, 15>::parse(&mut input.lines()).unwrap();
+
+    let costs = CostModel::default();
+    let facing_right = board.search_from(board.start, Direction::Right, &costs);
+    let facing_up = board.search_from(board.start, Direction::Up, &costs);
+    let from_end = board.search_from(board.end, Direction::Down, &costs);
+
+    assert_eq!(facing_right.cheapest_cost(board.end), Some(7036));
+    // Facing up saves the first turn
+    assert_eq!(facing_up.cheapest_cost(board.end), Some(6036));
+    assert_eq!(from_end.cheapest_cost(board.end), Some(0));
+    assert_eq!(board.find_cheapest_path(), 7036);
+}
+
+#[test]
+fn test_cost_models() {
+    let input = "#####
+#..E#
+#.#.#
+#S..#
+#####";
+
+    let board = Board::<5, 5>::parse(&mut input.lines()).unwrap();
+
+    let cost = |direction, costs: &CostModel<5, 5>| {
+        board
+            .search_from(board.start, direction, costs)
+            .cheapest_cost(board.end)
+    };
+
+    assert_eq!(cost(Direction::Right, &CostModel::default()), Some(1004));
+
+    let cheap_turns = CostModel {
+        turn: 1,
+        ..CostModel::default()
+    };
+    assert_eq!(cost(Direction::Right, &cheap_turns), Some(5));
+
+    // Facing away from the end we either turn twice or pay for the u-turn
+    assert_eq!(cost(Direction::Left, &CostModel::default()), Some(2004));
+    let u_turns = CostModel {
+        u_turn: Some(1),
+        ..CostModel::default()
+    };
+    assert_eq!(cost(Direction::Left, &u_turns), Some(1005));
+
+    // Making the bottom row expensive forces the path up the left side instead
+    let weighted = CostModel {
+        tile_weights: HashMap::from([(Position { x: 2, y: 3 }, 5000)]),
+        ..CostModel::default()
+    };
+    assert_eq!(cost(Direction::Right, &weighted), Some(2004));
+}
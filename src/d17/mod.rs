@@ -0,0 +1,449 @@
+const INPUT: &str = "./src/d17/input.txt";
+
+// Enough for any real program to halt, while still catching ones that loop forever
+const MAX_STEPS: usize = 1_000_000;
+
+use crate::parsing::{literal, separated, unsigned, ParseError, Parsed};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Opcode {
+    Adv,
+    Bxl,
+    Bst,
+    Jnz,
+    Bxc,
+    Out,
+    Bdv,
+    Cdv,
+}
+
+impl Opcode {
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        Some(match bits {
+            0 => Opcode::Adv,
+            1 => Opcode::Bxl,
+            2 => Opcode::Bst,
+            3 => Opcode::Jnz,
+            4 => Opcode::Bxc,
+            5 => Opcode::Out,
+            6 => Opcode::Bdv,
+            7 => Opcode::Cdv,
+            _ => return None,
+        })
+    }
+
+    pub const fn takes_combo(self) -> bool {
+        matches!(
+            self,
+            Opcode::Adv | Opcode::Bst | Opcode::Out | Opcode::Bdv | Opcode::Cdv
+        )
+    }
+}
+
+impl std::fmt::Display for Opcode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Opcode::Adv => "adv",
+                Opcode::Bxl => "bxl",
+                Opcode::Bst => "bst",
+                Opcode::Jnz => "jnz",
+                Opcode::Bxc => "bxc",
+                Opcode::Out => "out",
+                Opcode::Bdv => "bdv",
+                Opcode::Cdv => "cdv",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Instruction {
+    opcode: Opcode,
+    operand: u8,
+}
+
+impl std::fmt::Display for Instruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (self.opcode, self.operand) {
+            (Opcode::Bxc, _) => write!(f, "{}", self.opcode),
+            (opcode, 4) if opcode.takes_combo() => write!(f, "{opcode} A"),
+            (opcode, 5) if opcode.takes_combo() => write!(f, "{opcode} B"),
+            (opcode, 6) if opcode.takes_combo() => write!(f, "{opcode} C"),
+            (opcode, operand) => write!(f, "{opcode} {operand}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Registers {
+    a: u64,
+    b: u64,
+    c: u64,
+}
+
+impl std::fmt::Display for Registers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "A={} B={} C={}", self.a, self.b, self.c)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Program {
+    registers: Registers,
+    code: Vec<u8>,
+}
+
+fn parse_register<'a>(input: &'a str, name: &str) -> Parsed<'a, u64> {
+    let input = literal(input, "Register ")?;
+    let input = literal(input, name)?;
+    let input = literal(input, ": ")?;
+    let (value, input) = unsigned(input)?;
+
+    Ok((value, literal(input, "\n")?))
+}
+
+impl Program {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let error_at = |rest: &str, message| ParseError::at(input, rest, message);
+
+        let (a, rest) = parse_register(input, "A").map_err(|message| error_at(input, message))?;
+        let (b, rest) = parse_register(rest, "B").map_err(|message| error_at(rest, message))?;
+        let (c, rest) = parse_register(rest, "C").map_err(|message| error_at(rest, message))?;
+
+        let program = literal(rest, "\nProgram: ").map_err(|message| error_at(rest, message))?;
+        let (code, rest) = separated(program, ",", unsigned::<u8>)
+            .map_err(|message| error_at(program, message))?;
+
+        if !rest.trim_end().is_empty() {
+            return Err(error_at(rest, "Expected nothing after the program"));
+        }
+
+        if let Some(index) = code.iter().position(|bits| *bits > 7) {
+            let offset: usize = program
+                .split(',')
+                .take(index)
+                .map(|bits| bits.len() + 1)
+                .sum();
+            return Err(error_at(
+                &program[offset..],
+                "Expected the program to only contain 3-bit numbers",
+            ));
+        }
+
+        Ok(Self {
+            registers: Registers { a, b, c },
+            code,
+        })
+    }
+
+    pub fn disassemble(&self) -> Vec<String> {
+        self.code
+            .chunks(2)
+            .zip((0..).step_by(2))
+            .map(|(pair, address)| {
+                let line = match *pair {
+                    [opcode, operand] => Opcode::from_bits(opcode).map_or_else(
+                        || format!("??? {opcode} {operand}"),
+                        |opcode| Instruction { opcode, operand }.to_string(),
+                    ),
+                    [opcode] => format!("??? {opcode}"),
+                    _ => unreachable!("Chunks are one or two long"),
+                };
+                format!("{address:>3}: {line}")
+            })
+            .collect()
+    }
+
+    pub fn machine(&self) -> Machine<'_> {
+        self.machine_with_a(self.registers.a)
+    }
+
+    pub fn machine_with_a(&self, a: u64) -> Machine<'_> {
+        Machine {
+            code: &self.code,
+            registers: Registers {
+                a,
+                ..self.registers
+            },
+            pointer: 0,
+            steps: 0,
+            output: Vec::new(),
+        }
+    }
+}
+
+struct Machine<'a> {
+    code: &'a [u8],
+    registers: Registers,
+    pointer: usize,
+    steps: usize,
+    output: Vec<u8>,
+}
+
+impl Machine<'_> {
+    fn combo(&self, operand: u8) -> Result<u64, &'static str> {
+        Ok(match operand {
+            0..=3 => operand.into(),
+            4 => self.registers.a,
+            5 => self.registers.b,
+            6 => self.registers.c,
+            _ => return Err("Combo operand 7 is reserved"),
+        })
+    }
+
+    /// Runs a single instruction, or returns `None` once the program has halted
+    pub fn step(&mut self) -> Result<Option<Instruction>, &'static str> {
+        let Some(&[opcode, operand]) = self.code.get(self.pointer..self.pointer + 2) else {
+            return Ok(None);
+        };
+
+        if self.steps == MAX_STEPS {
+            return Err("Program didn't halt within the step limit");
+        }
+        self.steps += 1;
+
+        let opcode = Opcode::from_bits(opcode).ok_or("Expected a 3-bit opcode")?;
+        let instruction = Instruction { opcode, operand };
+        let combo = if opcode.takes_combo() {
+            self.combo(operand)?
+        } else {
+            0
+        };
+        let registers = &mut self.registers;
+
+        // Shifting past the width of A always leaves nothing
+        let divide = |a: u64| {
+            u32::try_from(combo)
+                .ok()
+                .and_then(|shift| a.checked_shr(shift))
+                .unwrap_or(0)
+        };
+
+        self.pointer += 2;
+
+        match opcode {
+            Opcode::Adv => registers.a = divide(registers.a),
+            Opcode::Bxl => registers.b ^= u64::from(operand),
+            Opcode::Bst => registers.b = combo % 8,
+            Opcode::Jnz if registers.a != 0 => self.pointer = operand.into(),
+            Opcode::Jnz => (),
+            Opcode::Bxc => registers.b ^= registers.c,
+            Opcode::Out => self
+                .output
+                .push((combo % 8).try_into().expect("Values mod 8 fit in a u8")),
+            Opcode::Bdv => registers.b = divide(registers.a),
+            Opcode::Cdv => registers.c = divide(registers.a),
+        }
+
+        Ok(Some(instruction))
+    }
+
+    pub fn run(mut self) -> Result<Vec<u8>, &'static str> {
+        while self.step()?.is_some() {}
+
+        Ok(self.output)
+    }
+
+    pub fn trace(mut self) -> Result<(Vec<u8>, Vec<String>), &'static str> {
+        let mut trace = Vec::new();
+
+        loop {
+            let pointer = self.pointer;
+            let Some(instruction) = self.step()? else {
+                break;
+            };
+
+            trace.push(format!(
+                "{pointer:>3}: {:<6} {}",
+                instruction.to_string(),
+                self.registers
+            ));
+        }
+
+        Ok((self.output, trace))
+    }
+}
+
+fn join_output(output: &[u8]) -> String {
+    output
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Works back from the last output, as looping with `adv 3` makes each output depend on only 3 more bits of A
+fn find_quine(program: &Program) -> Result<Option<u64>, &'static str> {
+    let instructions: Vec<_> = program.code.chunks(2).collect();
+    let count = |opcode: u8| instructions.iter().filter(|i| i[0] == opcode).count();
+
+    // Each output must only depend on the next 3 bits of A, in a single loop back to the start
+    if instructions.last() != Some(&&[3, 0][..])
+        || count(3) != 1
+        || count(5) != 1
+        || count(0) != 1
+        || !instructions.contains(&&[0, 3][..])
+    {
+        return Err("Expected a single loop that outputs once and shifts A by 3 bits");
+    }
+
+    let mut candidates = vec![0_u64];
+
+    for start in (0..program.code.len()).rev() {
+        let mut next = Vec::new();
+
+        for candidate in candidates {
+            for bits in 0..8 {
+                let Some(a) = candidate
+                    .checked_mul(8)
+                    .and_then(|value| value.checked_add(bits))
+                else {
+                    continue;
+                };
+
+                if program.machine_with_a(a).run()? == program.code[start..] {
+                    next.push(a);
+                }
+            }
+        }
+
+        candidates = next;
+    }
+
+    Ok(candidates.into_iter().min())
+}
+
+#[test]
+fn test_instructions() {
+    let run = |a, b, c, code: Vec<u8>| {
+        let program = Program {
+            registers: Registers { a, b, c },
+            code,
+        };
+        let mut machine = program.machine();
+        while machine.step().unwrap().is_some() {}
+        (machine.registers, machine.output)
+    };
+
+    assert_eq!(run(0, 0, 9, vec![2, 6]).0.b, 1);
+    assert_eq!(run(10, 0, 0, vec![5, 0, 5, 1, 5, 4]).1, [0, 1, 2]);
+
+    let (registers, output) = run(2024, 0, 0, vec![0, 1, 5, 4, 3, 0]);
+    assert_eq!(output, [4, 2, 5, 6, 7, 7, 7, 7, 3, 1, 0]);
+    assert_eq!(registers.a, 0);
+
+    assert_eq!(run(0, 29, 0, vec![1, 7]).0.b, 26);
+    assert_eq!(run(0, 2024, 43690, vec![4, 0]).0.b, 44354);
+
+    // Jumping back to the start while A is never changed would loop forever
+    let program = Program {
+        registers: Registers { a: 1, b: 0, c: 0 },
+        code: vec![3, 0],
+    };
+    assert!(program.machine().run().is_err());
+    assert!(program.machine().trace().is_err());
+}
+
+#[test]
+fn test_disassemble_and_trace() {
+    let program = Program::parse(
+        "Register A: 10
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0
+",
+    )
+    .unwrap();
+
+    assert_eq!(
+        program.disassemble(),
+        ["  0: adv 1", "  2: out A", "  4: jnz 0"]
+    );
+
+    let (output, trace) = program.machine().trace().unwrap();
+    assert_eq!(output, [5, 2, 1, 0]);
+    assert_eq!(
+        trace[..4],
+        [
+            "  0: adv 1  A=5 B=0 C=0",
+            "  2: out A  A=5 B=0 C=0",
+            "  4: jnz 0  A=5 B=0 C=0",
+            "  0: adv 1  A=2 B=0 C=0",
+        ]
+    );
+}
+
+#[test]
+fn test_part_one() {
+    let input = "Register A: 729
+Register B: 0
+Register C: 0
+
+Program: 0,1,5,4,3,0";
+
+    let program = Program::parse(input).unwrap();
+    let output = program.machine().run().unwrap();
+
+    assert_eq!(join_output(&output), "4,6,3,5,6,3,5,2,1,0");
+
+    let error = Program::parse(&input.replace("0,1,", "0,9,")).unwrap_err();
+    assert_eq!((error.line, error.column), (5, 12));
+    let error = Program::parse(&input.replace("B:", "D:")).unwrap_err();
+    assert_eq!(error.line, 2);
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let program = Program::parse(&input)?;
+
+    for line in program.disassemble() {
+        println!("{line}");
+    }
+
+    let (output, trace) = program.machine().trace()?;
+
+    for line in trace {
+        println!("{line}");
+    }
+
+    println!("Output was {}", join_output(&output));
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let input = "Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0";
+
+    let program = Program::parse(input).unwrap();
+
+    assert_eq!(find_quine(&program), Ok(Some(117_440)));
+    assert_eq!(program.machine_with_a(117_440).run().unwrap(), program.code);
+
+    // Only shifting A by 1 bit a loop breaks the search's assumptions
+    let program = Program::parse(&input.replace("0,3,", "0,1,")).unwrap();
+    assert!(find_quine(&program).is_err());
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let program = Program::parse(&input)?;
+
+    let Some(a) = find_quine(&program)? else {
+        return Err("No value of A makes the program output itself".into());
+    };
+
+    println!("Register A should start as {a}");
+
+    Ok(())
+}
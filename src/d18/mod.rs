@@ -0,0 +1,256 @@
+const INPUT: &str = "./src/d18/input.txt";
+
+use std::collections::VecDeque;
+
+use crate::parsing::{pair, unsigned, ParseError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Position<const SIZE: usize> {
+    x: u8,
+    y: u8,
+}
+
+impl<const SIZE: usize> Position<SIZE> {
+    pub fn neighbors(self) -> impl Iterator<Item = Self> {
+        [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .into_iter()
+            .filter_map(move |(dx, dy)| {
+                let x = self.x.checked_add_signed(dx)?;
+                let y = self.y.checked_add_signed(dy)?;
+
+                Position::try_from((x, y)).ok()
+            })
+    }
+}
+
+impl<const SIZE: usize> std::fmt::Display for Position<SIZE> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl<const SIZE: usize> TryFrom<(u8, u8)> for Position<SIZE> {
+    type Error = ();
+
+    fn try_from((x, y): (u8, u8)) -> Result<Self, Self::Error> {
+        if usize::from(x) >= SIZE || usize::from(y) >= SIZE {
+            return Err(());
+        }
+
+        Ok(Position { x, y })
+    }
+}
+
+impl<const SIZE: usize> TryFrom<(usize, usize)> for Position<SIZE> {
+    type Error = ();
+
+    fn try_from((x, y): (usize, usize)) -> Result<Self, Self::Error> {
+        let x = u8::try_from(x).map_err(|_| ())?;
+        let y = u8::try_from(y).map_err(|_| ())?;
+
+        Position::try_from((x, y))
+    }
+}
+
+struct MemorySpace<const SIZE: usize> {
+    falling: Vec<Position<SIZE>>,
+}
+
+impl<const SIZE: usize> MemorySpace<SIZE> {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut falling = Vec::new();
+        let mut rest = input;
+
+        // The bytes end at the first empty line
+        while !rest.is_empty() && !rest.starts_with('\n') {
+            let (position, after) = pair(rest, ",", unsigned::<u8>)
+                .map_err(|message| ParseError::at(input, rest, message))?;
+            let position = Position::try_from(position).map_err(|()| {
+                ParseError::at(input, rest, "Byte fell outside of the memory space")
+            })?;
+            falling.push(position);
+
+            rest = match after.strip_prefix('\n') {
+                Some(next) => next,
+                None if after.is_empty() => after,
+                None => {
+                    return Err(ParseError::at(
+                        input,
+                        after,
+                        "Expected only a position on each line",
+                    ))
+                }
+            };
+        }
+
+        Ok(Self { falling })
+    }
+
+    /// The fewest steps from the top left to the bottom right, after the first `fallen` bytes have landed
+    pub fn shortest_path(&self, fallen: usize) -> Option<usize> {
+        let mut blocked = [[false; SIZE]; SIZE];
+        for byte in self.falling.iter().take(fallen) {
+            blocked[usize::from(byte.y)][usize::from(byte.x)] = true;
+        }
+
+        let start = Position::<SIZE> { x: 0, y: 0 };
+        let exit = Position::try_from((SIZE - 1, SIZE - 1)).ok()?;
+
+        if blocked[0][0] {
+            return None;
+        }
+
+        // Blocked doubles as our visited set, as we never want to step on either again
+        blocked[0][0] = true;
+        let mut to_visit = VecDeque::from([(start, 0)]);
+
+        while let Some((position, steps)) = to_visit.pop_front() {
+            if position == exit {
+                return Some(steps);
+            }
+
+            for next in position.neighbors() {
+                let cell = &mut blocked[usize::from(next.y)][usize::from(next.x)];
+                if !*cell {
+                    *cell = true;
+                    to_visit.push_back((next, steps + 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Once the exit is cut off it stays cut off, so we binary search for how many bytes it takes
+    pub fn first_blocking_byte(&self) -> Option<Position<SIZE>> {
+        // The exit is reachable after `reachable` bytes, and not after `blocked` bytes
+        let mut reachable = 0;
+        let mut blocked = self.falling.len();
+
+        if self.shortest_path(blocked).is_some() {
+            return None;
+        }
+
+        while blocked - reachable > 1 {
+            let middle = reachable + (blocked - reachable) / 2;
+
+            if self.shortest_path(middle).is_some() {
+                reachable = middle;
+            } else {
+                blocked = middle;
+            }
+        }
+
+        Some(self.falling[blocked - 1])
+    }
+}
+
+#[test]
+fn test_part_one() {
+    let input = "5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
+
+    let memory = MemorySpace::<7>::parse(input).unwrap();
+
+    assert_eq!(memory.shortest_path(0), Some(12));
+    assert_eq!(memory.shortest_path(12), Some(22));
+    // Asking for more bytes than will ever fall just uses all of them
+    assert_eq!(memory.shortest_path(100), None);
+
+    let error = MemorySpace::<7>::parse("1,1\n2,2x\n3,3").err().unwrap();
+    assert_eq!((error.line, error.column), (2, 4));
+    let error = MemorySpace::<7>::parse("1,1\n7,2").err().unwrap();
+    assert_eq!((error.line, error.column), (2, 1));
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let memory = MemorySpace::<71>::parse(&input)?;
+
+    let Some(steps) = memory.shortest_path(1024) else {
+        return Err("The exit can't be reached".into());
+    };
+
+    println!("Reached the exit in {steps} steps");
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let input = "5,4
+4,2
+4,5
+3,0
+2,1
+6,3
+2,4
+1,5
+0,6
+3,3
+2,6
+5,1
+1,2
+5,5
+2,5
+6,5
+1,4
+0,4
+6,4
+1,1
+6,1
+1,0
+0,5
+1,6
+2,0";
+
+    let memory = MemorySpace::<7>::parse(input).unwrap();
+
+    let byte = memory.first_blocking_byte().unwrap();
+
+    assert_eq!(byte.to_string(), "6,1");
+    assert_eq!(memory.shortest_path(21), None);
+    assert!(memory.shortest_path(20).is_some());
+
+    // If the exit is still reachable once every byte has fallen, no byte blocks it
+    let memory = MemorySpace::<7>::parse("1,1\n2,2").unwrap();
+    assert_eq!(memory.first_blocking_byte(), None);
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let memory = MemorySpace::<71>::parse(&input)?;
+
+    let Some(byte) = memory.first_blocking_byte() else {
+        return Err("No byte cuts off the exit".into());
+    };
+
+    println!("The first byte to cut off the exit is {byte}");
+
+    Ok(())
+}
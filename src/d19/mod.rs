@@ -0,0 +1,150 @@
+const INPUT: &str = "./src/d19/input.txt";
+
+use crate::parsing::{literal, separated, ParseError, Parsed};
+
+fn stripes(input: &str) -> Parsed<'_, &str> {
+    let length = input.bytes().take_while(u8::is_ascii_lowercase).count();
+
+    if length == 0 {
+        return Err("Expected a towel");
+    }
+
+    Ok(input.split_at(length))
+}
+
+struct Onsen<'a> {
+    towels: Vec<&'a str>,
+    designs: Vec<&'a str>,
+}
+
+impl<'a> Onsen<'a> {
+    pub fn parse(input: &'a str) -> Result<Self, ParseError> {
+        let error_at = |rest: &str, message| ParseError::at(input, rest, message);
+
+        let (towels, rest) =
+            separated(input, ", ", stripes).map_err(|message| error_at(input, message))?;
+        let mut rest = literal(rest, "\n\n").map_err(|message| error_at(rest, message))?;
+
+        let mut designs = Vec::new();
+
+        // The designs end at the first empty line
+        while !rest.is_empty() && !rest.starts_with('\n') {
+            let (design, after) = stripes(rest).map_err(|message| error_at(rest, message))?;
+            designs.push(design);
+
+            rest = match after.strip_prefix('\n') {
+                Some(next) => next,
+                None if after.is_empty() => after,
+                None => return Err(error_at(after, "Expected each design to only be stripes")),
+            };
+        }
+
+        Ok(Self { towels, designs })
+    }
+
+    /// Counts the ways to line up towels to make the design, working back from its end
+    pub fn arrangements(&self, design: &str) -> u64 {
+        let mut ways = vec![0_u64; design.len() + 1];
+        ways[design.len()] = 1;
+
+        for start in (0..design.len()).rev() {
+            ways[start] = self
+                .towels
+                .iter()
+                .filter(|towel| design[start..].starts_with(**towel))
+                .map(|towel| ways[start + towel.len()])
+                .try_fold(0_u64, u64::checked_add)
+                .expect("Arrangements should fit in a u64");
+        }
+
+        ways[0]
+    }
+
+    pub fn possible_designs(&self) -> usize {
+        self.designs
+            .iter()
+            .filter(|design| self.arrangements(design) > 0)
+            .count()
+    }
+
+    pub fn total_arrangements(&self) -> u64 {
+        self.designs
+            .iter()
+            .map(|design| self.arrangements(design))
+            .try_fold(0_u64, u64::checked_add)
+            .expect("Arrangements should fit in a u64")
+    }
+}
+
+#[test]
+fn test_part_one() {
+    let input = "r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb
+";
+
+    let onsen = Onsen::parse(input).unwrap();
+
+    assert_eq!(onsen.towels.len(), 8);
+    assert_eq!(onsen.possible_designs(), 6);
+
+    let error = Onsen::parse("r, wr\n\nrwr\nrw-r").err().unwrap();
+    assert_eq!((error.line, error.column), (4, 3));
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let onsen = Onsen::parse(&input)?;
+
+    println!("{} designs are possible", onsen.possible_designs());
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let input = "r, wr, b, g, bwu, rb, gb, br
+
+brwrr
+bggr
+gbbr
+rrbgbr
+ubwu
+bwurrg
+brgr
+bbrgwb";
+
+    let onsen = Onsen::parse(input).unwrap();
+
+    let counts: Vec<_> = onsen
+        .designs
+        .iter()
+        .map(|design| onsen.arrangements(design))
+        .collect();
+
+    assert_eq!(counts, [2, 1, 4, 6, 0, 1, 2, 0]);
+    assert_eq!(onsen.total_arrangements(), 16);
+
+    // Splitting a run of one colour into ones and twos gives a Fibonacci number of arrangements
+    let input = format!("a, aa\n\n{}", "a".repeat(89));
+    let onsen = Onsen::parse(&input).unwrap();
+    assert_eq!(onsen.total_arrangements(), 2_880_067_194_370_816_120);
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let onsen = Onsen::parse(&input)?;
+
+    println!("There are {} arrangements", onsen.total_arrangements());
+
+    Ok(())
+}
@@ -1,136 +1,166 @@
 const INPUT: &str = "./src/d2/input.txt";
 
-fn validate_line<const TOLERANCE: u8>(line: &str) -> Result<(), ()> {
-    let levels = line
-        .split(' ')
-        .map(str::parse::<u8>)
-        .map(|result| result.map_err(|_| ()));
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    ParseFailure,
+    NoStep,
+    StepTooLarge { step: u8 },
+    DirectionFlip,
+}
 
-    let mut level_pairs = levels.clone().zip(levels.skip(1)).peekable();
+impl std::fmt::Display for Fault {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Fault::ParseFailure => write!(f, "not a number"),
+            Fault::NoStep => write!(f, "step of 0"),
+            Fault::StepTooLarge { step } => write!(f, "step of {step} is larger than 3"),
+            Fault::DirectionFlip => write!(f, "direction flipped"),
+        }
+    }
+}
 
-    let (first, second) = level_pairs
-        .peek()
-        .copied()
-        .expect("There should be at least two numbers in a line");
+/// Where and why a report is unsafe, pointing at the first bad level of the unmodified report
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct UnsafeLevel {
+    index: usize,
+    fault: Fault,
+}
 
-    // Need to either increase or decrease
-    if first == second {
-        return Err(());
-    }
-    let first_order = first?.cmp(&second?);
-
-    let mut faults = 0;
-
-    for (value, next) in level_pairs {
-        let value = value?;
-        let next = next?;
-        if first_order != value.cmp(&next) {
-            // Unsafe order
-            faults += 1;
-            if faults > TOLERANCE {
-                return Err(());
-            }
-            continue;
+fn find_fault(levels: &[u8]) -> Option<UnsafeLevel> {
+    let direction = levels.first()?.cmp(levels.get(1)?);
+
+    levels.windows(2).zip(1..).find_map(|(pair, index)| {
+        let fault = match pair[0].abs_diff(pair[1]) {
+            0 => Fault::NoStep,
+            step @ 4.. => Fault::StepTooLarge { step },
+            _ if pair[0].cmp(&pair[1]) != direction => Fault::DirectionFlip,
+            _ => return None,
+        };
+
+        Some(UnsafeLevel { index, fault })
+    })
+}
+
+/// Checks if the line is safe after removing at most `tolerance` levels, returning the removed levels
+fn validate_line(line: &str, tolerance: usize) -> Result<Vec<usize>, UnsafeLevel> {
+    let levels = line
+        .split(' ')
+        .enumerate()
+        .map(|(index, level)| {
+            level.parse::<u8>().map_err(|_| UnsafeLevel {
+                index,
+                fault: Fault::ParseFailure,
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let Some(fault) = find_fault(&levels) else {
+        return Ok(Vec::new());
+    };
+
+    for order in [Ordering::Less, Ordering::Greater] {
+        let is_safe_step = |from: u8, to: u8| from.cmp(&to) == order && from.abs_diff(to) <= 3;
+
+        // The fewest removals to end on each level, and the previous level we kept to get there
+        let mut removals: Vec<(usize, Option<usize>)> = Vec::with_capacity(levels.len());
+
+        for (index, level) in levels.iter().copied().enumerate() {
+            // Worst case we drop every level before this one
+            let fewest = levels[..index]
+                .iter()
+                .copied()
+                .zip(removals.iter().copied())
+                .enumerate()
+                .filter(|(_, (previous, _))| is_safe_step(*previous, level))
+                .map(|(previous_index, (_, (removed, _)))| {
+                    (removed + (index - previous_index - 1), Some(previous_index))
+                })
+                .fold((index, None), |best, candidate| {
+                    if candidate.0 < best.0 {
+                        candidate
+                    } else {
+                        best
+                    }
+                });
+
+            removals.push(fewest);
         }
 
-        if let 1..=3 = value.abs_diff(next) {
-            // Safe
+        let Some((fewest, last_kept)) = removals
+            .iter()
+            .enumerate()
+            .map(|(index, (removed, _))| (removed + (levels.len() - index - 1), index))
+            .min()
+        else {
+            continue;
+        };
+
+        if fewest > tolerance {
             continue;
         }
 
-        faults += 1;
-        if faults > TOLERANCE {
-            return Err(());
+        let mut kept = vec![false; levels.len()];
+        let mut next = Some(last_kept);
+        while let Some(index) = next {
+            kept[index] = true;
+            next = removals[index].1;
         }
+
+        return Ok(kept
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, kept)| (!kept).then_some(index))
+            .collect());
     }
 
-    Ok(())
+    Err(fault)
 }
 
-fn validate_lines_with_tolerance(line: &str) -> Result<(), ()> {
-    let mut values = line
-        .split(' ')
-        .map(str::parse::<u8>)
-        .map(|result| result.map_err(|_| ()))
-        .peekable();
-
-    let mut previous = values
-        .next()
-        .expect("There should be at least two values in the line")?;
-    // let _next = values.peek().copied().unwrap().unwrap();
-    let mut direction = values
-        .peek()
-        .copied()
-        .expect("There should be at least two values in a line")?
-        .cmp(&previous);
-
-    let mut values = values.enumerate().peekable();
-
-    let mut has_seen_faults = false;
-    let mut consumed = 1;
-
-    while let Some((index, next)) = values.next() {
-        let next = next?;
-
-        // Distance must be 1 to 3 otherwise we can drop the value immediately
-        match next.abs_diff(previous) {
-            1..=3 => (),
-            _ if has_seen_faults == false => {
-                has_seen_faults = true;
-                continue;
-            }
-            _ => return Err(()),
-        }
-
-        if next.cmp(&previous) != direction {
-            if has_seen_faults {
-                // We only tolerate one error
-                return Err(());
-            }
-            has_seen_faults = true;
-            // If we're on the third value, we could still recover by dropping either the second or third value
-            if index == 2 {
-                let Some((_, Ok(fourth))) = values.peek().copied() else {
-                    // We should be okay if we only have 3 values and we can just drop the third
-                    return Ok(());
-                };
-
-                if direction == fourth.cmp(&previous) {
-                    // In this case we drop the third value
-                    continue;
+fn annotate_line(line: &str, result: &Result<Vec<usize>, UnsafeLevel>) -> String {
+    match result {
+        Ok(removed) => line
+            .split(' ')
+            .enumerate()
+            .map(|(index, level)| {
+                if removed.contains(&index) {
+                    format!("[{level}]")
+                } else {
+                    level.to_owned()
                 }
-                if direction != fourth.cmp(&next) {
-                    // Then we retroactively drop the second value
-                    previous = next;
-                    direction = fourth.cmp(&next);
-                    continue;
-                }
-            }
-            // Otherwise we just drop this value
-            continue;
+            })
+            .collect::<Vec<_>>()
+            .join(" "),
+        Err(UnsafeLevel { index, fault }) => {
+            let offset: usize = line
+                .split(' ')
+                .take(*index)
+                .map(|level| level.len() + 1)
+                .sum();
+            format!("{line}\n{}^ {fault}", " ".repeat(offset))
         }
-
-        previous = next;
     }
-
-    Ok(())
 }
 
 #[test]
 fn validate_validate() {
-    assert_eq!(validate_line::<0>("11 15 16 18 20 21 23 26"), Err(()));
+    assert_eq!(
+        validate_line("11 15 16 18 20 21 23 26", 0),
+        Err(UnsafeLevel {
+            index: 1,
+            fault: Fault::StepTooLarge { step: 4 }
+        })
+    );
 }
 
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
     let file = std::fs::read_to_string(INPUT)?;
 
-    let mut safe = 0;
-    for line in file.lines() {
-        match validate_line::<0>(line) {
-            Ok(()) => safe += 1,
-            _ => continue,
-        }
-    }
+    let safe = file
+        .lines()
+        .filter(|line| validate_line(line, 0).is_ok())
+        .count();
 
     println!("Found {safe} reports");
 
@@ -142,9 +172,14 @@ pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
 
     let mut safe = 0;
     for line in file.lines() {
-        match validate_lines_with_tolerance(line) {
-            Ok(()) => safe += 1,
-            _ => continue,
+        let result = validate_line(line, 1);
+
+        if !result.as_ref().is_ok_and(Vec::is_empty) {
+            println!("{}", annotate_line(line, &result));
+        }
+
+        if result.is_ok() {
+            safe += 1;
         }
     }
 
@@ -156,99 +191,20 @@ pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
 #[test]
 fn test_tolorence() -> Result<(), Box<dyn std::error::Error>> {
     let file = std::fs::read_to_string(INPUT)?;
-    let mut safe_counts = Vec::with_capacity(10);
-
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<0>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<1>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<2>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<3>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<4>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<5>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<6>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
 
-        safe_counts.push(safe);
-    }
-    {
-        let mut safe = 0;
-        for line in file.lines() {
-            match validate_line::<7>(line) {
-                Ok(()) => safe += 1,
-                _ => continue,
-            }
-        }
-
-        safe_counts.push(safe);
-    }
+    let safe_counts: Vec<usize> = (0..8)
+        .map(|tolerance| {
+            file.lines()
+                .filter(|line| validate_line(line, tolerance).is_ok())
+                .count()
+        })
+        .collect();
 
     println!("Found counts {safe_counts:?}");
 
+    // Tolerating more removals can never make a report unsafe
+    assert!(safe_counts.windows(2).all(|pair| pair[0] <= pair[1]));
+
     Ok(())
 }
 
@@ -261,20 +217,48 @@ fn part_two_test_set() {
 8 6 4 4 1
 1 3 6 7 9";
 
-    let mut safe = 0;
-    let mut new_safe = 0;
-    for line in file.lines() {
-        match validate_line::<1>(line) {
-            Ok(()) => safe += 1,
-            _ => continue,
-        }
-        match validate_lines_with_tolerance(line) {
-            Ok(()) => new_safe += 1,
-            _ => continue,
-        }
-    }
+    let safe = |tolerance| {
+        file.lines()
+            .filter(|line| validate_line(line, tolerance).is_ok())
+            .count()
+    };
+
+    assert_eq!(safe(0), 2);
+    assert_eq!(safe(1), 4);
+}
 
-    println!("{safe} {new_safe}");
+#[test]
+fn test_tolerance_removes_any_level() {
+    // Dropping the first level, a middle level, or the last level
+    assert_eq!(validate_line("9 1 2 3", 1), Ok(vec![0]));
+    assert_eq!(validate_line("1 2 9 3 4", 1), Ok(vec![2]));
+    assert_eq!(validate_line("1 2 3 9", 1), Ok(vec![3]));
+    // Two faults need two removals
+    assert!(validate_line("1 9 2 9 3", 1).is_err());
+    assert_eq!(validate_line("1 9 2 9 3", 2), Ok(vec![1, 3]));
+    assert_eq!(
+        validate_line("1 2 x", 3),
+        Err(UnsafeLevel {
+            index: 2,
+            fault: Fault::ParseFailure
+        })
+    );
+}
 
-    assert_eq!(safe, 4);
+#[test]
+fn test_faults() {
+    let fault = |line| validate_line(line, 0).unwrap_err();
+
+    assert_eq!(fault("8 6 4 4 1").fault, Fault::NoStep);
+    assert_eq!(fault("8 6 4 4 1").index, 3);
+    assert_eq!(fault("1 3 2 4 5").fault, Fault::DirectionFlip);
+    assert_eq!(fault("1 3 2 4 5").index, 2);
+    assert_eq!(fault("9 7 6 2 1").fault, Fault::StepTooLarge { step: 4 });
+
+    let line = "1 3 2 4 5";
+    assert_eq!(annotate_line(line, &validate_line(line, 1)), "1 3 [2] 4 5");
+    assert_eq!(
+        annotate_line(line, &validate_line(line, 0)),
+        "1 3 2 4 5\n    ^ direction flipped"
+    );
 }
@@ -0,0 +1,256 @@
+const INPUT: &str = "./src/d20/input.txt";
+
+use std::collections::{BTreeMap, VecDeque};
+
+use crate::{
+    grid::{Grid, GridCell},
+    parsing::ParseError,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Track,
+    Wall,
+    Start,
+    End,
+}
+
+impl GridCell for Cell {
+    const MARKERS: &'static [char] = &['S', 'E'];
+
+    fn from_char(char: char) -> Option<Self> {
+        Some(match char {
+            '.' => Cell::Track,
+            '#' => Cell::Wall,
+            'S' => Cell::Start,
+            'E' => Cell::End,
+            _ => return None,
+        })
+    }
+
+    fn to_char(&self) -> char {
+        match self {
+            Cell::Track => '.',
+            Cell::Wall => '#',
+            Cell::Start => 'S',
+            Cell::End => 'E',
+        }
+    }
+}
+
+type Distances = Vec<Vec<Option<usize>>>;
+
+struct Racetrack {
+    grid: Grid<Cell>,
+}
+
+impl Racetrack {
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        Ok(Self {
+            grid: Grid::parse(&mut input.lines(), None)?,
+        })
+    }
+
+    fn distances_from(&self, from: (usize, usize)) -> Distances {
+        let mut distances: Distances = self
+            .grid
+            .rows
+            .iter()
+            .map(|row| vec![None; row.len()])
+            .collect();
+
+        distances[from.1][from.0] = Some(0);
+        let mut to_visit = VecDeque::from([from]);
+
+        while let Some((x, y)) = to_visit.pop_front() {
+            let distance = distances[y][x].expect("Queued cells have a distance");
+
+            for (nx, ny) in [
+                (x + 1, y),
+                (x, y + 1),
+                (x.wrapping_sub(1), y),
+                (x, y.wrapping_sub(1)),
+            ] {
+                let Some(cell) = self.grid.rows.get(ny).and_then(|row| row.get(nx)) else {
+                    continue;
+                };
+
+                if *cell != Cell::Wall && distances[ny][nx].is_none() {
+                    distances[ny][nx] = Some(distance + 1);
+                    to_visit.push_back((nx, ny));
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// How many cheats save each amount of time, for cheats lasting up to `max_cheat` picoseconds
+    pub fn cheat_savings(&self, max_cheat: usize) -> BTreeMap<usize, usize> {
+        let start = self.grid.marker('S').expect("The start is a marker");
+        let end = self.grid.marker('E').expect("The end is a marker");
+
+        let from_start = self.distances_from(start);
+        let to_end = self.distances_from(end);
+
+        let mut savings = BTreeMap::new();
+
+        let Some(fair) = from_start[end.1][end.0] else {
+            return savings;
+        };
+
+        let radius = isize::try_from(max_cheat).expect("Cheats should fit in an isize");
+        let offsets: Vec<(isize, isize)> = (-radius..=radius)
+            .flat_map(|dy| {
+                let remaining = radius - dy.abs();
+                (-remaining..=remaining).map(move |dx| (dx, dy))
+            })
+            .collect();
+
+        for (y, row) in from_start.iter().enumerate() {
+            for (x, before) in row.iter().enumerate() {
+                let Some(before) = before else {
+                    continue;
+                };
+
+                for (dx, dy) in &offsets {
+                    let after = x
+                        .checked_add_signed(*dx)
+                        .zip(y.checked_add_signed(*dy))
+                        .and_then(|(to_x, to_y)| *to_end.get(to_y)?.get(to_x)?);
+                    let Some(after) = after else {
+                        continue;
+                    };
+
+                    let cheated = before + dx.unsigned_abs() + dy.unsigned_abs() + after;
+
+                    if let Some(saved) = fair.checked_sub(cheated).filter(|saved| *saved > 0) {
+                        *savings.entry(saved).or_default() += 1;
+                    }
+                }
+            }
+        }
+
+        savings
+    }
+
+    pub fn cheats_saving_at_least(&self, max_cheat: usize, threshold: usize) -> usize {
+        self.cheat_savings(max_cheat)
+            .range(threshold..)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+#[test]
+fn test_part_one() {
+    let input = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
+
+    let track = Racetrack::parse(input).unwrap();
+
+    let savings: Vec<_> = track.cheat_savings(2).into_iter().collect();
+
+    assert_eq!(
+        savings,
+        [
+            (2, 14),
+            (4, 14),
+            (6, 2),
+            (8, 4),
+            (10, 2),
+            (12, 3),
+            (20, 1),
+            (36, 1),
+            (38, 1),
+            (40, 1),
+            (64, 1)
+        ]
+    );
+    assert_eq!(track.cheats_saving_at_least(2, 20), 5);
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let track = Racetrack::parse(&input)?;
+
+    let cheats = track.cheats_saving_at_least(2, 100);
+
+    println!("Found {cheats} cheats saving at least 100 picoseconds");
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let input = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############";
+
+    let track = Racetrack::parse(input).unwrap();
+
+    let savings: Vec<_> = track
+        .cheat_savings(20)
+        .range(50..)
+        .map(|(saved, count)| (*saved, *count))
+        .collect();
+
+    assert_eq!(
+        savings,
+        [
+            (50, 32),
+            (52, 31),
+            (54, 29),
+            (56, 39),
+            (58, 25),
+            (60, 23),
+            (62, 20),
+            (64, 19),
+            (66, 12),
+            (68, 14),
+            (70, 12),
+            (72, 22),
+            (74, 4),
+            (76, 3)
+        ]
+    );
+    assert_eq!(track.cheats_saving_at_least(20, 50), 285);
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let track = Racetrack::parse(&input)?;
+
+    let cheats = track.cheats_saving_at_least(20, 100);
+
+    println!("Found {cheats} cheats of up to 20 picoseconds saving at least 100 picoseconds");
+
+    Ok(())
+}
@@ -0,0 +1,226 @@
+const INPUT: &str = "./src/d21/input.txt";
+
+use std::collections::HashMap;
+
+struct Keypad {
+    keys: HashMap<char, (i8, i8)>,
+    /// The empty corner no robot arm may ever pass over
+    gap: (i8, i8),
+}
+
+impl Keypad {
+    fn from_rows(rows: &[&str]) -> Self {
+        let mut keys = HashMap::new();
+        let mut gap = None;
+
+        for (row, y) in rows.iter().zip(0..) {
+            for (key, x) in row.chars().zip(0..) {
+                if key == ' ' {
+                    gap = Some((x, y));
+                } else {
+                    keys.insert(key, (x, y));
+                }
+            }
+        }
+
+        Self {
+            keys,
+            gap: gap.expect("Keypads have a gap"),
+        }
+    }
+
+    pub fn numeric() -> Self {
+        Self::from_rows(&["789", "456", "123", " 0A"])
+    }
+
+    pub fn directional() -> Self {
+        Self::from_rows(&[" ^A", "<v>"])
+    }
+
+    /// Zigzagging is never cheaper than all of one direction then the other, so at most two paths are worth trying
+    pub fn paths(&self, from: char, to: char) -> Vec<String> {
+        let (from_x, from_y) = self.keys[&from];
+        let (to_x, to_y) = self.keys[&to];
+
+        let horizontal_key = if to_x < from_x { '<' } else { '>' };
+        let vertical_key = if to_y < from_y { '^' } else { 'v' };
+        let horizontal = horizontal_key
+            .to_string()
+            .repeat(from_x.abs_diff(to_x).into());
+        let vertical = vertical_key
+            .to_string()
+            .repeat(from_y.abs_diff(to_y).into());
+
+        let mut paths = Vec::with_capacity(2);
+
+        // Moving horizontally first turns the corner at the target column on our row
+        if (to_x, from_y) != self.gap {
+            paths.push(format!("{horizontal}{vertical}A"));
+        }
+        if (from_x, to_y) != self.gap {
+            let path = format!("{vertical}{horizontal}A");
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        paths
+    }
+}
+
+struct KeypadChain {
+    numeric: Keypad,
+    directional: Keypad,
+    robots: usize,
+    /// The fewest human presses to move from one directional key to another and press it, by depth
+    costs: HashMap<(char, char, usize), u64>,
+}
+
+impl KeypadChain {
+    pub fn new(robots: usize) -> Self {
+        Self {
+            numeric: Keypad::numeric(),
+            directional: Keypad::directional(),
+            robots,
+            costs: HashMap::new(),
+        }
+    }
+
+    /// Every arm above rests on A after each press, so each move only depends on its two keys
+    fn sequence_cost(&mut self, keys: &str, depth: usize) -> u64 {
+        std::iter::once('A')
+            .chain(keys.chars())
+            .zip(keys.chars())
+            .map(|(from, to)| self.cost(from, to, depth))
+            .sum()
+    }
+
+    fn cost(&mut self, from: char, to: char, depth: usize) -> u64 {
+        if depth == 0 {
+            // The human presses the key directly
+            return 1;
+        }
+
+        if let Some(cost) = self.costs.get(&(from, to, depth)) {
+            return *cost;
+        }
+
+        let cost = self
+            .directional
+            .paths(from, to)
+            .iter()
+            .map(|path| self.sequence_cost(path, depth - 1))
+            .min()
+            .expect("Every key can reach every other key");
+
+        self.costs.insert((from, to, depth), cost);
+
+        cost
+    }
+
+    pub fn code_cost(&mut self, code: &str) -> u64 {
+        std::iter::once('A')
+            .chain(code.chars())
+            .zip(code.chars())
+            .map(|(from, to)| {
+                self.numeric
+                    .paths(from, to)
+                    .iter()
+                    .map(|path| self.sequence_cost(path, self.robots))
+                    .min()
+                    .expect("Every key can reach every other key")
+            })
+            .sum()
+    }
+
+    pub fn complexity(&mut self, code: &str) -> Result<u64, &'static str> {
+        if code
+            .chars()
+            .any(|key| !self.numeric.keys.contains_key(&key))
+        {
+            return Err("Expected codes to only use numeric keypad keys");
+        }
+
+        let value: u64 = code
+            .trim_end_matches('A')
+            .parse()
+            .map_err(|_| "Expected the code to be a number followed by A")?;
+
+        Ok(self.code_cost(code) * value)
+    }
+}
+
+fn complexity_sum(input: &str, robots: usize) -> Result<u64, &'static str> {
+    let mut chain = KeypadChain::new(robots);
+
+    input
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .map(|code| chain.complexity(code))
+        .sum()
+}
+
+#[test]
+fn test_paths() {
+    let numeric = Keypad::numeric();
+
+    assert_eq!(numeric.paths('A', '0'), ["<A"]);
+    assert_eq!(numeric.paths('2', '9'), [">^^A", "^^>A"]);
+    // Going left along the bottom row first would cross the gap
+    assert_eq!(numeric.paths('A', '1'), ["^<<A"]);
+    assert_eq!(numeric.paths('7', '0'), [">vvvA"]);
+
+    let directional = Keypad::directional();
+    assert_eq!(directional.paths('A', '<'), ["v<<A"]);
+    assert_eq!(directional.paths('<', '^'), [">^A"]);
+    assert_eq!(directional.paths('v', 'v'), ["A"]);
+}
+
+#[test]
+fn test_part_one() {
+    let input = "029A
+980A
+179A
+456A
+379A";
+
+    let mut chain = KeypadChain::new(2);
+
+    let lengths: Vec<_> = input.lines().map(|code| chain.code_cost(code)).collect();
+
+    assert_eq!(lengths, [68, 60, 68, 64, 64]);
+    assert_eq!(KeypadChain::new(0).code_cost("029A"), 12);
+    assert_eq!(KeypadChain::new(1).code_cost("029A"), 28);
+    assert_eq!(complexity_sum(input, 2), Ok(126_384));
+}
+
+pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let sum = complexity_sum(&input, 2)?;
+
+    println!("Sum of complexities was {sum}");
+
+    Ok(())
+}
+
+#[test]
+fn test_part_two() {
+    let input = "029A
+980A
+179A
+456A
+379A";
+
+    assert_eq!(complexity_sum(input, 25), Ok(154_115_708_116_294));
+}
+
+pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
+    let input = std::fs::read_to_string(INPUT)?;
+
+    let sum = complexity_sum(&input, 25)?;
+
+    println!("Sum of complexities was {sum}");
+
+    Ok(())
+}
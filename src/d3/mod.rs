@@ -1,135 +1,161 @@
-use std::iter::Peekable;
-
 const INPUT: &str = "./src/d3/input.txt";
 
-fn parse_number(iter: &mut Peekable<impl Iterator<Item = char>>) -> Option<u32> {
-    let mut number = iter.peek().and_then(|char| char.to_digit(10))?;
-    iter.next();
-
-    let Some(second_digit) = iter.peek().and_then(|char| char.to_digit(10)) else {
-        return Some(number);
-    };
-    iter.next();
-    number = number * 10 + second_digit;
-
-    let Some(third_digit) = iter.peek().and_then(|char| char.to_digit(10)) else {
-        return Some(number);
-    };
-    iter.next();
+use crate::parsing::{literal, unsigned_max_digits};
 
-    Some(number * 10 + third_digit)
+#[derive(Debug, Clone, Copy)]
+enum Effect {
+    Enable,
+    Disable,
+    Evaluate(fn(&[u32]) -> i64),
 }
 
-fn parse_next_mul(iter: &mut Peekable<impl Iterator<Item = char>>) -> Option<(u32, u32)> {
-    'outer_loop: loop {
-        for char in "mul(".chars() {
-            if iter.peek().copied()? != char {
-                return None;
-            }
+#[derive(Debug, Clone, Copy)]
+struct InstructionSpec {
+    name: &'static str,
+    operands: usize,
+    /// How many digits each operand may have, at least one is always required
+    max_digits: usize,
+    effect: Effect,
+}
 
-            iter.next();
-        }
+impl InstructionSpec {
+    fn parse<'a>(&self, index: usize, input: &'a str) -> Option<(Instruction, &'a str)> {
+        let mut input = literal(literal(input, self.name).ok()?, "(").ok()?;
+        let mut operands = Vec::with_capacity(self.operands);
 
-        let Some(first) = parse_number(iter) else {
-            continue 'outer_loop;
-        };
+        for index in 0..self.operands {
+            if index > 0 {
+                input = literal(input, ",").ok()?;
+            }
 
-        if iter.peek().copied()? != ',' {
-            continue 'outer_loop;
+            let (operand, rest) = unsigned_max_digits(input, self.max_digits).ok()?;
+            operands.push(operand);
+            input = rest;
         }
-        iter.next();
 
-        let Some(second) = parse_number(iter) else {
-            continue 'outer_loop;
-        };
+        let input = literal(input, ")").ok()?;
 
-        if iter.peek().copied()? != ')' {
-            continue 'outer_loop;
-        }
-        iter.next();
-
-        return Some((first, second));
+        Some((
+            Instruction {
+                spec: index,
+                name: self.name,
+                operands,
+            },
+            input,
+        ))
     }
 }
 
-enum Condition {
-    Do,
-    Dont,
+struct InstructionSet {
+    specs: Vec<InstructionSpec>,
 }
 
-/// Will try to parse next condition, note does not consume any characters if first char does not match
-fn parse_next_condition(iter: &mut Peekable<impl Iterator<Item = char>>) -> Option<Condition> {
-    for char in "do".chars() {
-        if iter.peek().copied()? != char {
-            return None;
+impl InstructionSet {
+    pub fn new(specs: impl IntoIterator<Item = InstructionSpec>) -> Self {
+        Self {
+            specs: specs.into_iter().collect(),
         }
-
-        iter.next();
     }
 
-    match iter.peek() {
-        Some('n') => (),
-        None | Some(_) => return Some(Condition::Do),
+    pub fn standard() -> Self {
+        Self::new([
+            InstructionSpec {
+                name: "mul",
+                operands: 2,
+                max_digits: 3,
+                effect: Effect::Evaluate(|operands| {
+                    i64::from(operands[0]) * i64::from(operands[1])
+                }),
+            },
+            InstructionSpec {
+                name: "do",
+                operands: 0,
+                max_digits: 0,
+                effect: Effect::Enable,
+            },
+            InstructionSpec {
+                name: "don't",
+                operands: 0,
+                max_digits: 0,
+                effect: Effect::Disable,
+            },
+        ])
     }
 
-    for char in "n't".chars() {
-        if iter.peek().copied()? != char {
-            return None;
-        }
-
-        iter.next();
+    fn parse_instruction<'a>(&self, input: &'a str) -> Option<(Instruction, &'a str)> {
+        self.specs
+            .iter()
+            .enumerate()
+            .find_map(|(index, spec)| spec.parse(index, input))
     }
 
-    Some(Condition::Dont)
-}
+    pub fn tokenize<'a>(&'a self, input: &'a str) -> impl Iterator<Item = Token> + 'a {
+        let mut offset = 0;
 
-fn parse_all_muls(input: &str) -> u32 {
-    let mut chars = input.chars().peekable();
-    let mut sum = 0;
+        std::iter::from_fn(move || {
+            while offset < input.len() {
+                let rest = &input[offset..];
 
-    while chars.peek().is_some() {
-        if let Some((a, b)) = parse_next_mul(&mut chars) {
-            sum += a * b
-        } else {
-            chars.next();
-        }
-    }
+                if let Some((instruction, remaining)) = self.parse_instruction(rest) {
+                    let token = Token {
+                        offset,
+                        instruction,
+                    };
+                    offset = input.len() - remaining.len();
+                    return Some(token);
+                }
 
-    sum
-}
+                // Skip a whole char so we stay on a char boundary
+                offset += rest.chars().next().map_or(1, char::len_utf8);
+            }
 
-fn parse_conditional_muls(input: &str) -> u32 {
-    let mut chars = input.chars().peekable();
-    let mut sum = 0;
-    let mut enabled = true;
+            None
+        })
+    }
 
-    while chars.peek().is_some() {
-        match parse_next_condition(&mut chars) {
-            Some(Condition::Do) => {
-                enabled = true;
-            }
-            Some(Condition::Dont) => {
-                enabled = false;
-            }
-            None if enabled == false => {
-                // In this case we know a mul would be invalid, so we need to swallow the next iter value
-                chars.next();
+    /// Sums the evaluated instructions, only respecting enabling and disabling if `conditional` is set
+    pub fn interpret(&self, tokens: impl Iterator<Item = Token>, conditional: bool) -> i64 {
+        let mut sum = 0;
+        let mut enabled = true;
+
+        for Token { instruction, .. } in tokens {
+            match self.specs[instruction.spec].effect {
+                Effect::Evaluate(evaluate) if enabled || !conditional => {
+                    sum += evaluate(&instruction.operands);
+                }
+                Effect::Evaluate(_) => (),
+                Effect::Enable => enabled = true,
+                Effect::Disable => enabled = false,
             }
-            None => (),
         }
 
-        if enabled == false {
-            continue;
-        }
-
-        if let Some((a, b)) = parse_next_mul(&mut chars) {
-            sum += a * b;
-        } else {
-            chars.next();
-        }
+        sum
     }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Instruction {
+    /// Index of the spec it was parsed with, as names may be shared between specs
+    spec: usize,
+    name: &'static str,
+    operands: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Token {
+    /// Byte offset of the instruction in the input
+    offset: usize,
+    instruction: Instruction,
+}
+
+fn parse_all_muls(input: &str) -> i64 {
+    let set = InstructionSet::standard();
+    set.interpret(set.tokenize(input), false)
+}
 
-    sum
+fn parse_conditional_muls(input: &str) -> i64 {
+    let set = InstructionSet::standard();
+    set.interpret(set.tokenize(input), true)
 }
 
 #[test]
@@ -143,15 +169,83 @@ fn test_part_one() {
 
 #[test]
 fn test_part_of_input() {
-    let mut  input = "%why();how()*-],+!mul(696,865)why()from()how():,;{where()mul(170,685)who()how()*from(881,957)?&select()mul(894,569):mul(648,114);[:from(657,891)how()mul(740,402)".chars().peekable();
+    let input = "%why();how()*-],+!mul(696,865)why()from()how():,;{where()mul(170,685)who()how()*from(881,957)?&select()mul(894,569):mul(648,114);[:from(657,891)how()mul(740,402)";
 
     let pairs = [(696, 865), (170, 685), (894, 569), (648, 114), (740, 402)];
 
-    for expected in pairs {
-        let found = parse_next_mul(&mut input);
+    let set = InstructionSet::standard();
+    let found: Vec<_> = set
+        .tokenize(input)
+        .map(|token| token.instruction.operands)
+        .collect();
 
-        assert_eq!(Some(expected), found);
-    }
+    assert_eq!(found, pairs.map(|(a, b)| vec![a, b]));
+}
+
+#[test]
+fn test_tokenize() {
+    let input = "done()do()mul(1,2)don't()mul(1234,5)xdon't";
+
+    let set = InstructionSet::standard();
+    let tokens: Vec<_> = set
+        .tokenize(input)
+        .map(|token| (token.offset, token.instruction.name))
+        .collect();
+
+    assert_eq!(tokens, [(6, "do"), (10, "mul"), (18, "don't")]);
+}
+
+#[test]
+fn test_custom_instructions() {
+    let input = "add(1,2)sub(10,20)mul(2,3)neg(5)don't()add(100,1)do()sum3(1,2,3)add(12345,1)";
+
+    let set = InstructionSet::new(InstructionSet::standard().specs.into_iter().chain([
+        InstructionSpec {
+            name: "add",
+            operands: 2,
+            max_digits: 5,
+            effect: Effect::Evaluate(|operands| i64::from(operands[0]) + i64::from(operands[1])),
+        },
+        InstructionSpec {
+            name: "sub",
+            operands: 2,
+            max_digits: 3,
+            effect: Effect::Evaluate(|operands| i64::from(operands[0]) - i64::from(operands[1])),
+        },
+        InstructionSpec {
+            name: "sum3",
+            operands: 3,
+            max_digits: 1,
+            effect: Effect::Evaluate(|operands| operands.iter().copied().map(i64::from).sum()),
+        },
+    ]));
+
+    // neg is not registered, so it's skipped like any other corruption
+    assert_eq!(
+        set.interpret(set.tokenize(input), true),
+        3 - 10 + 6 + 6 + 12346
+    );
+    assert_eq!(
+        set.interpret(set.tokenize(input), false),
+        3 - 10 + 6 + 101 + 6 + 12346
+    );
+
+    // The same name with a different number of operands is a separate instruction
+    let set = InstructionSet::new([
+        InstructionSpec {
+            name: "add",
+            operands: 3,
+            max_digits: 3,
+            effect: Effect::Evaluate(|operands| operands.iter().copied().map(i64::from).sum()),
+        },
+        InstructionSpec {
+            name: "add",
+            operands: 2,
+            max_digits: 3,
+            effect: Effect::Evaluate(|operands| i64::from(operands[0]) + i64::from(operands[1])),
+        },
+    ]);
+    assert_eq!(set.interpret(set.tokenize("add(1,2)add(1,2,3)"), false), 9);
 }
 
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
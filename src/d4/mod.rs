@@ -1,86 +1,185 @@
 const INPUT: &str = "./src/d4/input.txt";
 
-const EXPECTED_CHAIN: &str = "XMAS";
+use std::io::IsTerminal;
 
 type AsciiGrid<'a> = &'a [&'a [u8]];
 
+const DIRECTIONS: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 struct Position {
     x: usize,
     y: usize,
 }
 
-fn find_valid_chains(
-    from: Position,
-    height: usize,
-    width: usize,
-) -> impl Iterator<Item = impl IntoIterator<Item = Position>> {
-    let dirs = [
-        (-1, -1),
-        (0, -1),
-        (1, -1),
-        (-1, 0),
-        (1, 0),
-        (-1, 1),
-        (0, 1),
-        (1, 1),
-    ];
-
-    dirs.into_iter().filter_map(move |(dx, dy)| {
-        let mut out = [from; 4];
-
-        for (vector, out) in (1..=3).zip(out.iter_mut().skip(1)) {
-            let x = from.x.checked_add_signed(vector * dx)?;
-
-            if x >= width {
-                return None;
-            }
+impl Position {
+    fn offset(self, dx: isize, dy: isize, height: usize, width: usize) -> Option<Position> {
+        let x = self.x.checked_add_signed(dx)?;
 
-            let y = from.y.checked_add_signed(vector * dy)?;
+        if x >= width {
+            return None;
+        }
 
-            if y >= height {
-                return None;
-            }
+        let y = self.y.checked_add_signed(dy)?;
 
-            *out = Position { x, y };
+        if y >= height {
+            return None;
         }
 
-        Some(out)
+        Some(Position { x, y })
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+struct WordMatch {
+    start: Position,
+    /// `None` for one letter words, which read the same in every direction
+    direction: Option<(isize, isize)>,
+    length: usize,
+}
+
+impl WordMatch {
+    pub fn positions(self) -> impl Iterator<Item = Position> {
+        let (dx, dy) = self.direction.unwrap_or((0, 0));
+        (0..self.length).map(move |step| {
+            let step = step.cast_signed();
+            Position {
+                x: self.start.x.wrapping_add_signed(step * dx),
+                y: self.start.y.wrapping_add_signed(step * dy),
+            }
+        })
+    }
+}
+
+fn find_valid_chains(
+    from: Position,
+    length: usize,
+    height: usize,
+    width: usize,
+) -> impl Iterator<Item = (Option<(isize, isize)>, Vec<Position>)> {
+    let directions = if length > 1 {
+        DIRECTIONS.map(Some).to_vec()
+    } else {
+        vec![None]
+    };
+
+    directions.into_iter().filter_map(move |direction| {
+        let (dx, dy) = direction.unwrap_or((0, 0));
+        let chain = (0..length)
+            .map(|step| {
+                from.offset(
+                    step.cast_signed() * dx,
+                    step.cast_signed() * dy,
+                    height,
+                    width,
+                )
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some((direction, chain))
     })
 }
 
 #[test]
 fn sanity_valid_chains() {
-    for chain in find_valid_chains(Position { x: 2, y: 2 }, 10, 10) {
-        println!("Chain {:?}", chain.into_iter().collect::<Vec<_>>());
+    for chain in find_valid_chains(Position { x: 2, y: 2 }, 4, 10, 10) {
+        println!("Chain {chain:?}");
     }
+
+    // From a corner only three directions have room
+    assert_eq!(
+        find_valid_chains(Position { x: 0, y: 0 }, 4, 10, 10).count(),
+        3
+    );
 }
 
-fn find_xmas_in_grid(input: AsciiGrid) -> usize {
-    let mut xmases = 0;
+fn find_word(input: AsciiGrid, word: &[u8]) -> Vec<WordMatch> {
+    let Some(first) = word.first() else {
+        return Vec::new();
+    };
+
+    let mut matches = Vec::new();
     for (y, row) in input.iter().enumerate() {
         for (x, cell) in row.iter().enumerate() {
-            let mut chain = EXPECTED_CHAIN.as_bytes().iter().copied();
-            if Some(*cell) != chain.next() {
+            if cell != first {
                 continue;
             }
 
-            xmases += find_valid_chains(Position { x, y }, input.len(), input[0].len())
-                .filter_map(|from| {
-                    from.into_iter()
-                        .skip(1)
-                        .zip(chain.clone())
-                        .all(|(position, expected)| {
+            let start = Position { x, y };
+
+            matches.extend(
+                find_valid_chains(start, word.len(), input.len(), row.len())
+                    .filter(|(_, chain)| {
+                        chain.iter().zip(word).all(|(position, expected)| {
                             input.get(position.y).and_then(|row| row.get(position.x))
-                                == Some(&expected)
+                                == Some(expected)
                         })
-                        .then_some(())
-                })
-                .count();
+                    })
+                    .map(|(direction, _)| WordMatch {
+                        start,
+                        direction,
+                        length: word.len(),
+                    }),
+            );
+        }
+    }
+
+    matches
+}
+
+fn find_xmas_in_grid(input: AsciiGrid) -> Vec<WordMatch> {
+    find_word(input, b"XMAS")
+}
+
+/// With `colorize`, matched letters are colored by how many matches they are part of
+fn render_matches(
+    input: AsciiGrid,
+    matches: impl IntoIterator<Item = impl IntoIterator<Item = Position>>,
+    colorize: bool,
+) -> String {
+    let mut overlaps: Vec<Vec<usize>> = input.iter().map(|row| vec![0; row.len()]).collect();
+
+    for position in matches.into_iter().flatten() {
+        overlaps[position.y][position.x] += 1;
+    }
+
+    let mut output = String::with_capacity(input.iter().map(|row| row.len() + 1).sum());
+
+    for (row, overlaps) in input.iter().zip(overlaps) {
+        for (cell, overlaps) in row.iter().zip(overlaps) {
+            let cell = char::from(*cell);
+            let color = match (overlaps, colorize) {
+                (0, _) => {
+                    output.push('.');
+                    continue;
+                }
+                (_, false) => None,
+                (1, true) => Some("\x1b[32m"),
+                (2, true) => Some("\x1b[33m"),
+                (_, true) => Some("\x1b[31m"),
+            };
+
+            if let Some(color) = color {
+                output.push_str(color);
+                output.push(cell);
+                output.push_str("\x1b[0m");
+            } else {
+                output.push(cell);
+            }
         }
+        output.push('\n');
     }
 
-    xmases
+    output
 }
 
 #[test]
@@ -100,7 +199,24 @@ MXMXAXMASX";
 
     let xmases = find_xmas_in_grid(&lines);
 
-    assert_eq!(xmases, 18);
+    assert_eq!(xmases.len(), 18);
+
+    let expected = "....XXMAS.
+.SAMXMS...
+...S..A...
+..A.A.MS.X
+XMASAMX.MM
+X.....XA.A
+S.S.S.S.SS
+.A.A.A.A.A
+..M.M.M.MM
+.X.X.XMASX
+";
+
+    assert_eq!(
+        render_matches(&lines, xmases.into_iter().map(WordMatch::positions), false),
+        expected
+    );
 }
 
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
@@ -110,55 +226,105 @@ pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
 
     let xmases = find_xmas_in_grid(&lines);
 
-    println!("Found {xmases} xmases");
+    print!(
+        "{}",
+        render_matches(
+            &lines,
+            xmases.iter().copied().map(WordMatch::positions),
+            std::io::stdout().is_terminal()
+        )
+    );
+    println!("Found {} xmases", xmases.len());
 
     Ok(())
 }
 
-fn find_cross_lines(origin: Position, height: usize, width: usize) -> Option<[[Position; 3]; 2]> {
-    if origin.x == 0 || origin.x >= width - 1 {
-        return None;
-    }
-    if origin.y == 0 || origin.y >= height - 1 {
-        return None;
-    }
-
-    let diagonal = [[(1, 1), (0, 0), (-1, -1)], [(1, -1), (0, 0), (-1, 1)]];
-
-    Some(diagonal.map(move |row| {
-        row.map(move |(dx, dy)| Position {
-            x: origin.x.checked_add_signed(dy).unwrap(),
-            y: origin.y.checked_add_signed(dx).unwrap(),
-        })
-    }))
+/// A 2D pattern to look for in the grid, where `None` matches any letter
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    rows: Vec<Vec<Option<u8>>>,
 }
 
-fn find_x_mas_in_grid(input: AsciiGrid) -> usize {
-    let mut crosses = 0;
-
-    for (y, row) in input.iter().enumerate() {
-        'cell_loop: for (x, cell) in row.iter().enumerate() {
-            if *cell != b'A' {
-                continue;
-            }
+impl Pattern {
+    pub fn parse(input: &str) -> Self {
+        Self {
+            rows: input
+                .lines()
+                .map(|line| {
+                    line.bytes()
+                        .map(|cell| if cell == b'.' { None } else { Some(cell) })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
 
-            let Some(set) = find_cross_lines(Position { x, y }, input.len(), row.len()) else {
-                continue;
-            };
+    pub fn cells(&self, origin: Position) -> impl Iterator<Item = (Position, u8)> + '_ {
+        self.rows.iter().enumerate().flat_map(move |(dy, row)| {
+            row.iter().enumerate().filter_map(move |(dx, cell)| {
+                cell.map(|cell| {
+                    (
+                        Position {
+                            x: origin.x + dx,
+                            y: origin.y + dy,
+                        },
+                        cell,
+                    )
+                })
+            })
+        })
+    }
 
-            let found_values = set.map(|row| row.map(|position| input[position.y][position.x]));
+    fn matches_at(&self, input: AsciiGrid, origin: Position) -> bool {
+        self.cells(origin).all(|(position, expected)| {
+            input.get(position.y).and_then(|row| row.get(position.x)) == Some(&expected)
+        })
+    }
 
-            for row in found_values {
-                if row != [b'M', b'A', b'S'] && row != [b'S', b'A', b'M'] {
-                    continue 'cell_loop;
+    /// Finds the top left corner of every place the pattern matches
+    pub fn find_in(&self, input: AsciiGrid) -> Vec<Position> {
+        let height = self.rows.len();
+        let width = self.rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        let mut matches = Vec::new();
+        for y in 0..=input.len().saturating_sub(height) {
+            for x in 0..=input
+                .get(y)
+                .map_or(0, |row| row.len().saturating_sub(width))
+            {
+                let origin = Position { x, y };
+                if self.matches_at(input, origin) {
+                    matches.push(origin);
                 }
             }
-
-            crosses += 1;
         }
+
+        matches
     }
+}
+
+fn x_mas_patterns() -> [Pattern; 4] {
+    [
+        "M.S\n.A.\nM.S",
+        "M.M\n.A.\nS.S",
+        "S.M\n.A.\nS.M",
+        "S.S\n.A.\nM.M",
+    ]
+    .map(Pattern::parse)
+}
 
-    crosses
+fn find_x_mas_in_grid(input: AsciiGrid) -> Vec<Vec<Position>> {
+    x_mas_patterns()
+        .iter()
+        .flat_map(|pattern| {
+            pattern.find_in(input).into_iter().map(|origin| {
+                pattern
+                    .cells(origin)
+                    .map(|(position, _)| position)
+                    .collect()
+            })
+        })
+        .collect()
 }
 
 #[test]
@@ -178,7 +344,21 @@ MXMXAXMASX";
 
     let xmases = find_x_mas_in_grid(&lines);
 
-    assert_eq!(xmases, 9);
+    assert_eq!(xmases.len(), 9);
+
+    let expected = ".M.S......
+..A..MSMS.
+.M.S.MAA..
+..A.ASMSM.
+.M.S.M....
+..........
+S.S.S.S.S.
+.A.A.A.A..
+M.M.M.M.M.
+..........
+";
+
+    assert_eq!(render_matches(&lines, xmases, false), expected);
 }
 
 pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
@@ -188,7 +368,96 @@ pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
 
     let xmases = find_x_mas_in_grid(&lines);
 
-    println!("Found {xmases} xmases");
+    print!(
+        "{}",
+        render_matches(
+            &lines,
+            xmases.iter().map(|cross| cross.iter().copied()),
+            std::io::stdout().is_terminal()
+        )
+    );
+    println!("Found {} xmases", xmases.len());
 
     Ok(())
 }
+
+#[test]
+fn test_find_any_word() {
+    let file = "ABCD
+BXAB
+CADC
+DBCA";
+
+    let lines: Vec<_> = file.lines().map(str::as_bytes).collect();
+
+    assert_eq!(
+        find_word(&lines, b"ABCD"),
+        [
+            WordMatch {
+                start: Position { x: 0, y: 0 },
+                direction: Some((1, 0)),
+                length: 4
+            },
+            WordMatch {
+                start: Position { x: 0, y: 0 },
+                direction: Some((0, 1)),
+                length: 4
+            },
+        ]
+    );
+    assert_eq!(find_word(&lines, b"AB").len(), 6);
+    assert_eq!(find_word(&lines, b"XAC").len(), 0);
+    assert_eq!(
+        find_word(&lines, b"X"),
+        [WordMatch {
+            start: Position { x: 1, y: 1 },
+            direction: None,
+            length: 1
+        }]
+    );
+    assert_eq!(
+        find_word(&lines, b"DAA"),
+        [
+            WordMatch {
+                start: Position { x: 3, y: 0 },
+                direction: Some((-1, 1)),
+                length: 3
+            },
+            WordMatch {
+                start: Position { x: 0, y: 3 },
+                direction: Some((1, -1)),
+                length: 3
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_find_pattern() {
+    let file = "ABAB
+XAXA
+ABAB";
+
+    let lines: Vec<_> = file.lines().map(str::as_bytes).collect();
+
+    let pattern = Pattern::parse("A.A\n.A");
+
+    assert_eq!(
+        pattern.find_in(&lines),
+        [Position { x: 0, y: 0 }, Position { x: 1, y: 1 }]
+    );
+}
+
+#[test]
+fn test_render_overlaps() {
+    let file = "XMASAMX";
+
+    let lines: Vec<_> = file.lines().map(str::as_bytes).collect();
+
+    let matches = find_xmas_in_grid(&lines);
+
+    assert_eq!(
+        render_matches(&lines, matches.iter().copied().map(WordMatch::positions), true),
+        "\x1b[32mX\x1b[0m\x1b[32mM\x1b[0m\x1b[32mA\x1b[0m\x1b[33mS\x1b[0m\x1b[32mA\x1b[0m\x1b[32mM\x1b[0m\x1b[32mX\x1b[0m\n"
+    );
+}
@@ -1,36 +1,46 @@
 const INPUT: &str = "./src/d6/input.txt";
 
+use crate::{
+    grid::{Grid, GridCell},
+    parsing::ParseError,
+};
+
 #[derive(Debug, PartialEq, Eq)]
 enum Cell {
     Empty,
     Obstacle,
+    /// Where the guard starts, which is otherwise empty
+    Guard,
 }
 
-type Map = Vec<Vec<Cell>>;
+impl GridCell for Cell {
+    const MARKERS: &'static [char] = &['^'];
 
-fn parse_map(input: &str) -> (Map, Position) {
-    let mut map = Vec::new();
-    let mut guard = None;
-
-    for (y, line) in input.lines().enumerate() {
-        let mut map_line = Vec::with_capacity(line.as_bytes().len());
-
-        for (x, cell) in line.as_bytes().iter().enumerate() {
-            map_line.push(match cell {
-                b'.' => Cell::Empty,
-                b'#' => Cell::Obstacle,
-                b'^' => {
-                    guard = Some(Position { x, y });
-                    Cell::Empty
-                }
-                unknown => panic!("Saw unknown char {unknown}"),
-            });
-        }
+    fn from_char(char: char) -> Option<Self> {
+        Some(match char {
+            '.' => Cell::Empty,
+            '#' => Cell::Obstacle,
+            '^' => Cell::Guard,
+            _ => return None,
+        })
+    }
 
-        map.push(map_line);
+    fn to_char(&self) -> char {
+        match self {
+            Cell::Empty => '.',
+            Cell::Obstacle => '#',
+            Cell::Guard => '^',
+        }
     }
+}
+
+type Map = Vec<Vec<Cell>>;
 
-    (map, guard.expect("Should have seen guard on map"))
+fn parse_map(input: &str) -> Result<(Map, Position), ParseError> {
+    let grid = Grid::<Cell>::parse(&mut input.lines(), None)?;
+    let (x, y) = grid.marker('^').expect("The guard is a marker");
+
+    Ok((grid.rows, Position { x, y }))
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -83,8 +93,8 @@ impl Position {
     }
 }
 
-fn run_sim(input: &str) -> usize {
-    let (map, mut guard_position) = parse_map(input);
+fn run_sim(input: &str) -> Result<usize, ParseError> {
+    let (map, mut guard_position) = parse_map(input)?;
     let height = map.len();
     let width = map[0].len();
     let mut direction = Direction::Up;
@@ -105,7 +115,7 @@ fn run_sim(input: &str) -> usize {
         visited.insert(next);
     }
 
-    visited.len()
+    Ok(visited.len())
 }
 
 #[test]
@@ -121,13 +131,47 @@ fn run_test_sim() {
 #.........
 ......#...";
 
-    assert_eq!(run_sim(input), 41);
+    assert_eq!(run_sim(input), Ok(41));
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = parse_map("..#\n.^x\n...").unwrap_err();
+    assert_eq!((error.line, error.column), (2, 3));
+    assert_eq!(
+        error.to_string(),
+        "line 2, column 3: Found unexpected cell value x\n2 | .^x\n  |   ^"
+    );
+
+    let error = parse_map("..#\n.^.\n^..").unwrap_err();
+    assert_eq!((error.line, error.column), (3, 1));
+
+    let error = parse_map("..#\n...\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 2, column 1: Did not find ^ in the map\n2 | ...\n  | ^"
+    );
+}
+
+#[test]
+fn test_map_round_trip() {
+    let input = "....#.....
+.........#
+..#.......
+.#..^.....
+#.........
+";
+
+    let grid = Grid::<Cell>::parse(&mut input.lines(), None).unwrap();
+
+    assert_eq!((grid.width(), grid.height()), (10, 5));
+    assert_eq!(grid.to_string(), input);
 }
 
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
     let input = std::fs::read_to_string(INPUT)?;
 
-    let unique = run_sim(&input);
+    let unique = run_sim(&input)?;
 
     println!("Ran {unique} unique steps");
 
@@ -2,15 +2,14 @@ const INPUT: &str = "./src/d7/input.txt";
 
 use std::cmp::Ordering;
 
-fn line_solvable(input: &str) -> Result<u128, ()> {
-    let (result, inputs) = input.split_once(':').unwrap();
-    let result = result.parse().unwrap();
+use crate::parsing::{literal, separated, unsigned};
 
-    let inputs: Vec<u64> = inputs
-        .trim_start()
-        .split(' ')
-        .map(|input| input.parse().expect("All lines should be valid numbers"))
-        .collect();
+fn line_solvable(input: &str) -> Result<u128, ()> {
+    let (result, input) = unsigned::<u64>(input).expect("Lines should start with the result");
+    let input = literal(input, ": ").expect("The result should be followed by a colon");
+    let (inputs, rest) =
+        separated(input, " ", unsigned::<u64>).expect("All lines should be valid numbers");
+    assert!(rest.is_empty(), "All lines should be valid numbers");
 
     recursive_check(&result, 0, inputs.iter().copied()).map(|()| result.into())
 }
@@ -82,6 +81,12 @@ fn test_part_one() {
     assert_eq!(sum_of_solvable_lines(input), 3749);
 }
 
+#[test]
+#[should_panic = "All lines should be valid numbers"]
+fn test_trailing_input() {
+    sum_of_solvable_lines("190: 10 19x");
+}
+
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
     let input = std::fs::read_to_string(INPUT)?;
 
@@ -2,10 +2,13 @@ const INPUT: &str = "./src/d8/input.txt";
 
 use std::collections::{HashMap, HashSet};
 
+use crate::combinatorics::unordered_pairs;
+
 #[derive(Debug, Clone, Copy)]
+// Positions are u8, so the difference between two of them always fits in an i16
 struct Vector {
-    dx: i8,
-    dy: i8,
+    dx: i16,
+    dy: i16,
 }
 
 impl Vector {
@@ -15,6 +18,37 @@ impl Vector {
             dy: -self.dy,
         }
     }
+
+    pub fn reduced(self) -> Self {
+        let divisor = gcd(self.dx.unsigned_abs(), self.dy.unsigned_abs());
+
+        if divisor <= 1 {
+            return self;
+        }
+
+        let divisor = i16::try_from(divisor).expect("The divisor is at most 255");
+        Self {
+            dx: self.dx / divisor,
+            dy: self.dy / divisor,
+        }
+    }
+}
+
+const fn gcd(mut a: u16, mut b: u16) -> u16 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AntipodeRule {
+    /// Only the two positions twice as far from one antenna as the other
+    Pairs,
+    /// Every position a whole number of antenna distances away
+    Harmonics,
+    /// Every grid point on the line
+    Lattice,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -25,11 +59,9 @@ struct Position {
 
 impl Position {
     pub fn vector_to(self, other: Position) -> Vector {
-        let dx = i16::from(self.x) - i16::from(other.x);
-        let dy = i16::from(self.y) - i16::from(other.y);
         Vector {
-            dx: dx.try_into().expect("Input is only 50x50"),
-            dy: dy.try_into().expect("Input is only 50x50"),
+            dx: i16::from(self.x) - i16::from(other.x),
+            dy: i16::from(self.y) - i16::from(other.y),
         }
     }
 
@@ -42,15 +74,13 @@ impl Position {
     }
 
     pub fn add(self, vector: Vector) -> Option<Position> {
-        let x = self.x.checked_add_signed(vector.dx)?;
-        let y = self.y.checked_add_signed(vector.dy)?;
+        let x = (i16::from(self.x) + vector.dx).try_into().ok()?;
+        let y = (i16::from(self.y) + vector.dy).try_into().ok()?;
         Some(Position { x, y })
     }
 
     pub fn sub(self, vector: Vector) -> Option<Position> {
-        let x = self.x.checked_add_signed(-vector.dx)?;
-        let y = self.y.checked_add_signed(-vector.dy)?;
-        Some(Position { x, y })
+        self.add(vector.reverse())
     }
 
     pub fn antipodes_with(
@@ -88,8 +118,10 @@ impl Position {
         other: Position,
         height: u8,
         width: u8,
+        lattice: bool,
     ) -> impl Iterator<Item = Position> {
         let delta = self.vector_to(other);
+        let delta = if lattice { delta.reduced() } else { delta };
 
         // First we run to the last valid direction one way, so we can go back the other and find all valid positions
 
@@ -129,18 +161,24 @@ impl Iterator for PositionIterator {
 fn test_infinite_antipodes() {
     let a = Position { x: 1, y: 1 };
     let b = Position { x: 2, y: 2 };
-    for (found, i) in a.infinite_antipodes_with(b, 10, 10).zip((0..1).chain(3..)) {
-        assert_eq!(found.x, i);
-        assert_eq!(found.y, i);
-    }
+    let found: Vec<_> = a.infinite_antipodes_with(b, 10, 10, false).collect();
+    assert_eq!(
+        found,
+        (0..10).map(|i| Position { x: i, y: i }).collect::<Vec<_>>()
+    );
 
     let a = Position { x: 3, y: 3 };
-    let b = Position { x: 4, y: 4 };
-    let expected = (0..=2).chain(5..);
-    for (found, expected) in a.infinite_antipodes_with(b, 10, 10).zip(expected) {
-        assert_eq!(found.x, expected);
-        assert_eq!(found.y, expected);
-    }
+    let b = Position { x: 5, y: 5 };
+    let found: Vec<_> = a.infinite_antipodes_with(b, 10, 10, false).collect();
+    assert_eq!(
+        found,
+        (0..5)
+            .map(|i| Position {
+                x: 2 * i + 1,
+                y: 2 * i + 1
+            })
+            .collect::<Vec<_>>()
+    );
 
     let a = Position { x: 6, y: 5 };
     let b = Position { x: 9, y: 9 };
@@ -149,10 +187,53 @@ fn test_infinite_antipodes() {
         Position { x: 6, y: 5 },
         Position { x: 9, y: 9 },
     ];
-    for (found, expected) in a.infinite_antipodes_with(b, 12, 12).zip(expected) {
-        assert_eq!(expected, found);
-        println!("{found:?} did match {expected:?}");
-    }
+    let found: Vec<_> = a.infinite_antipodes_with(b, 12, 12, false).collect();
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn test_reduced() {
+    let step = Vector { dx: -200, dy: 0 }.reduced();
+    assert_eq!((step.dx, step.dy), (-1, 0));
+
+    let step = Vector { dx: 4, dy: -6 }.reduced();
+    assert_eq!((step.dx, step.dy), (2, -3));
+
+    // Antennas can be further apart than an i8 reaches on larger maps
+    let a = Position { x: 10, y: 0 };
+    let b = Position { x: 210, y: 0 };
+    let found: Vec<_> = a.infinite_antipodes_with(b, 255, 255, false).collect();
+    assert_eq!(found, [a, b]);
+}
+
+#[test]
+fn test_lattice_antipodes() {
+    let a = Position { x: 0, y: 0 };
+    let b = Position { x: 2, y: 4 };
+
+    let harmonics: Vec<_> = a.infinite_antipodes_with(b, 10, 10, false).collect();
+    assert_eq!(
+        harmonics,
+        [(0, 0), (2, 4), (4, 8)].map(|(x, y)| Position { x, y })
+    );
+
+    let lattice: Vec<_> = a.infinite_antipodes_with(b, 10, 10, true).collect();
+    assert_eq!(
+        lattice,
+        [(0, 0), (1, 2), (2, 4), (3, 6), (4, 8)].map(|(x, y)| Position { x, y })
+    );
+}
+
+fn grid_size(input: &str) -> Result<(u8, u8), std::num::TryFromIntError> {
+    // Inputs can end with blank lines, which are not part of the map
+    let height = input
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .count()
+        .try_into()?;
+    let width = input.lines().next().map_or(0, str::len).try_into()?;
+
+    Ok((height, width))
 }
 
 fn find_positions(input: &str) -> impl IntoIterator<Item = (u8, Vec<Position>)> {
@@ -174,41 +255,79 @@ fn find_positions(input: &str) -> impl IntoIterator<Item = (u8, Vec<Position>)>
     map.into_iter()
 }
 
-struct IterWithRemaining<I>
-where
-    I: Iterator + Clone,
-{
-    iter: I,
+fn find_antipodes(
+    positions: &[Position],
+    height: u8,
+    width: u8,
+) -> impl Iterator<Item = Position> + '_ {
+    unordered_pairs(positions)
+        .flat_map(move |(start, end)| start.antipodes_with(*end, height, width))
 }
 
-impl<I> IterWithRemaining<I>
-where
-    I: Iterator + Clone,
-{
-    fn new(iter: I) -> Self {
-        IterWithRemaining { iter }
-    }
+fn antipodes_by_frequency(
+    input: &str,
+    rule: AntipodeRule,
+) -> Result<HashMap<u8, HashSet<Position>>, std::num::TryFromIntError> {
+    let (height, width) = grid_size(input)?;
+
+    Ok(find_positions(input)
+        .into_iter()
+        .map(|(frequency, positions)| {
+            let positions = &positions;
+            let antipodes = match rule {
+                AntipodeRule::Pairs => find_antipodes(positions, height, width).collect(),
+                AntipodeRule::Harmonics => {
+                    find_all_antipodes(positions, height, width, false).collect()
+                }
+                AntipodeRule::Lattice => {
+                    find_all_antipodes(positions, height, width, true).collect()
+                }
+            };
+
+            (frequency, antipodes)
+        })
+        .collect())
 }
 
-impl<I> Iterator for IterWithRemaining<I>
-where
-    I: Iterator + Clone,
-{
-    type Item = (I::Item, I);
+fn count_antipodes(by_frequency: &HashMap<u8, HashSet<Position>>) -> usize {
+    by_frequency
+        .values()
+        .flatten()
+        .collect::<HashSet<_>>()
+        .len()
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.iter.next().map(|item| (item, self.iter.clone()))
+/// Draws the map with every antipode as `#`, unless an antenna is in the same spot
+fn render_antipodes(input: &str, by_frequency: &HashMap<u8, HashSet<Position>>) -> String {
+    let antipodes: HashSet<_> = by_frequency.values().flatten().copied().collect();
+
+    let mut output = String::with_capacity(input.len() + 1);
+
+    for (line, y) in input.lines().zip(0..) {
+        for (char, x) in line.chars().zip(0..) {
+            if char == '.' && antipodes.contains(&Position { x, y }) {
+                output.push('#');
+            } else {
+                output.push(char);
+            }
+        }
+        output.push('\n');
     }
+
+    output
 }
 
-fn find_antipodes<'a>(
-    positions: impl Iterator<Item = Position> + Clone + 'a,
-    height: u8,
-    width: u8,
-) -> impl Iterator<Item = Position> + 'a {
-    IterWithRemaining::new(positions).flat_map(move |(start, remaining)| {
-        remaining.flat_map(move |end| start.antipodes_with(end, height, width))
-    })
+fn print_breakdown(by_frequency: &HashMap<u8, HashSet<Position>>) {
+    let mut frequencies: Vec<_> = by_frequency.iter().collect();
+    frequencies.sort_unstable_by_key(|(frequency, _)| **frequency);
+
+    for (frequency, antipodes) in frequencies {
+        println!(
+            "'{}' has {} antipodes",
+            char::from(*frequency),
+            antipodes.len()
+        );
+    }
 }
 
 #[test]
@@ -226,41 +345,48 @@ fn test_part_one() {
 ............
 ............";
 
-    let mut antipodes = HashSet::new();
+    let expected = "......#....#
+...#....0...
+....#0....#.
+..#....0....
+....0....#..
+.#....A.....
+...#........
+#......#....
+........A...
+.........A..
+..........#.
+..........#.
+";
 
-    for (_, positions) in find_positions(input) {
-        for antipode in find_antipodes(positions.into_iter(), 12, 12) {
-            antipodes.insert(antipode);
-        }
-    }
+    let by_frequency = antipodes_by_frequency(input, AntipodeRule::Pairs).unwrap();
 
-    assert_eq!(antipodes.len(), 14);
+    assert_eq!(by_frequency[&b'0'].len(), 10);
+    assert_eq!(by_frequency[&b'A'].len(), 5);
+    assert_eq!(count_antipodes(&by_frequency), 14);
+    assert_eq!(render_antipodes(input, &by_frequency), expected);
 }
 
 pub fn part_one() -> Result<(), Box<dyn std::error::Error>> {
     let input = std::fs::read_to_string(INPUT)?;
 
-    let mut antipodes = HashSet::new();
-
-    for (_, positions) in find_positions(&input) {
-        for antipode in find_antipodes(positions.into_iter(), 50, 50) {
-            antipodes.insert(antipode);
-        }
-    }
+    let by_frequency = antipodes_by_frequency(&input, AntipodeRule::Pairs)?;
 
-    println!("Found {} antipodes", antipodes.len());
+    print!("{}", render_antipodes(&input, &by_frequency));
+    print_breakdown(&by_frequency);
+    println!("Found {} antipodes", count_antipodes(&by_frequency));
 
     Ok(())
 }
 
-fn find_all_antipodes<'a>(
-    positions: impl Iterator<Item = Position> + Clone + 'a,
+fn find_all_antipodes(
+    positions: &[Position],
     height: u8,
     width: u8,
-) -> impl Iterator<Item = Position> + 'a {
-    IterWithRemaining::new(positions).flat_map(move |(start, remaining)| {
-        remaining.flat_map(move |end| start.infinite_antipodes_with(end, height, width))
-    })
+    lattice: bool,
+) -> impl Iterator<Item = Position> + '_ {
+    unordered_pairs(positions)
+        .flat_map(move |(start, end)| start.infinite_antipodes_with(*end, height, width, lattice))
 }
 
 #[test]
@@ -278,7 +404,7 @@ fn test_part_two() {
 ............
 ............";
 
-    let expected: Vec<&'static str> = "##....#....#
+    let expected = "##....#....#
 .#.#....0...
 ..#.#0....#.
 ..##...0....
@@ -289,39 +415,55 @@ fn test_part_two() {
 ..#.....A...
 ....#....A..
 .#........#.
-...#......##"
-        .lines()
-        .collect();
-
-    let mut antipodes = HashSet::new();
-
-    for (char, positions) in find_positions(input) {
-        for antipode in find_all_antipodes(positions.into_iter(), 12, 12) {
-            let in_expected = expected[usize::from(antipode.y)].as_bytes()[usize::from(antipode.x)];
-            let char = char::from_u32(char as u32).unwrap();
-            assert!(
-                in_expected != b'.',
-                "Should not have generated {antipode:?} for '{char}'"
-            );
-            antipodes.insert(antipode);
-        }
-    }
+...#......##
+";
 
-    assert_eq!(antipodes.len(), 34);
+    let by_frequency = antipodes_by_frequency(input, AntipodeRule::Harmonics).unwrap();
+
+    assert_eq!(render_antipodes(input, &by_frequency), expected);
+    assert_eq!(count_antipodes(&by_frequency), 34);
 }
 
 pub fn part_two() -> Result<(), Box<dyn std::error::Error>> {
     let input = std::fs::read_to_string(INPUT)?;
 
-    let mut antipodes = HashSet::new();
+    let by_frequency = antipodes_by_frequency(&input, AntipodeRule::Harmonics)?;
 
-    for (_, positions) in find_positions(&input) {
-        for antipode in find_all_antipodes(positions.into_iter(), 50, 50) {
-            antipodes.insert(antipode);
-        }
-    }
+    print!("{}", render_antipodes(&input, &by_frequency));
+    print_breakdown(&by_frequency);
+    println!("Found {} antipodes", count_antipodes(&by_frequency));
+
+    let lattice = antipodes_by_frequency(&input, AntipodeRule::Lattice)?;
 
-    println!("Found {} antipodes", antipodes.len());
+    println!(
+        "Found {} antipodes counting every grid point on the lines",
+        count_antipodes(&lattice)
+    );
 
     Ok(())
 }
+
+#[test]
+fn test_lattice_rule() {
+    let input = "......
+.a....
+......
+...a..
+......";
+
+    // The antennas are two apart on each axis, so the lattice also finds the point between them
+    let harmonics = antipodes_by_frequency(input, AntipodeRule::Harmonics).unwrap();
+    assert_eq!(count_antipodes(&harmonics), 2);
+
+    let by_frequency = antipodes_by_frequency(input, AntipodeRule::Lattice).unwrap();
+
+    assert_eq!(
+        render_antipodes(input, &by_frequency),
+        "#.....
+.a....
+..#...
+...a..
+....#.
+"
+    );
+}
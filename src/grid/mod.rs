@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use crate::parsing::ParseError;
+
+pub trait GridCell: Sized {
+    /// Characters that must appear exactly once in a map, like a start or a robot
+    const MARKERS: &'static [char] = &[];
+
+    fn from_char(char: char) -> Option<Self>;
+
+    fn to_char(&self) -> char;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<C> {
+    pub rows: Vec<Vec<C>>,
+    pub markers: HashMap<char, (usize, usize)>,
+}
+
+impl<C: GridCell> Grid<C> {
+    /// Parses a map of exactly `size`, or up to the first empty line if no size is given
+    pub fn parse<'a>(
+        lines: &mut impl Iterator<Item = &'a str>,
+        size: Option<(usize, usize)>,
+    ) -> Result<Self, ParseError> {
+        let mut rows: Vec<Vec<C>> = Vec::new();
+        let mut markers = HashMap::new();
+        let mut last_line = (0, "");
+
+        loop {
+            let y = rows.len();
+
+            if size.is_some_and(|(_, height)| y == height) {
+                break;
+            }
+
+            let Some(text) = lines
+                .next()
+                .filter(|text| size.is_some() || !text.is_empty())
+            else {
+                if let Some((_, height)) = size {
+                    let (line, text) = last_line;
+                    return Err(ParseError::new(
+                        text,
+                        line,
+                        text.chars().count(),
+                        format!("Expected {height} lines in the map"),
+                    ));
+                }
+                break;
+            };
+            last_line = (y, text);
+
+            let width = size.map(|(width, _)| width).or(rows.first().map(Vec::len));
+            let mut row = Vec::with_capacity(width.unwrap_or(text.len()));
+
+            for (x, char) in text.chars().enumerate() {
+                if let Some(width) = width.filter(|width| x == *width) {
+                    return Err(ParseError::new(
+                        text,
+                        y,
+                        x,
+                        format!("Expected the line to only contain {width} cells"),
+                    ));
+                }
+
+                let Some(cell) = C::from_char(char) else {
+                    return Err(ParseError::new(
+                        text,
+                        y,
+                        x,
+                        format!("Found unexpected cell value {char}"),
+                    ));
+                };
+
+                if C::MARKERS.contains(&char) {
+                    if let Some((first_x, first_y)) = markers.insert(char, (x, y)) {
+                        return Err(ParseError::new(
+                            text,
+                            y,
+                            x,
+                            format!(
+                                "Expected only one {char}, but already found one at line {}, column {}",
+                                first_y + 1,
+                                first_x + 1
+                            ),
+                        ));
+                    }
+                }
+
+                row.push(cell);
+            }
+
+            if let Some(width) = width.filter(|width| row.len() < *width) {
+                return Err(ParseError::new(
+                    text,
+                    y,
+                    row.len(),
+                    format!("Expected {width} cells in the line"),
+                ));
+            }
+
+            rows.push(row);
+        }
+
+        if let Some(missing) = C::MARKERS.iter().find(|char| !markers.contains_key(char)) {
+            let (line, text) = last_line;
+            return Err(ParseError::new(
+                text,
+                line,
+                0,
+                format!("Did not find {missing} in the map"),
+            ));
+        }
+
+        Ok(Self { rows, markers })
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, Vec::len)
+    }
+
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[must_use]
+    pub fn marker(&self, char: char) -> Option<(usize, usize)> {
+        self.markers.get(&char).copied()
+    }
+
+    pub fn error_at(&self, (x, y): (usize, usize), message: impl Into<String>) -> ParseError {
+        let text: String = self.rows[y].iter().map(GridCell::to_char).collect();
+        ParseError::new(&text, y, x, message)
+    }
+
+    #[must_use]
+    pub fn into_array<const WIDTH: usize, const HEIGHT: usize>(
+        self,
+    ) -> Option<[[C; WIDTH]; HEIGHT]> {
+        let rows: Vec<[C; WIDTH]> = self
+            .rows
+            .into_iter()
+            .map(|row| row.try_into().ok())
+            .collect::<Option<_>>()?;
+
+        rows.try_into().ok()
+    }
+}
+
+impl<C: GridCell> std::fmt::Display for Grid<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in &self.rows {
+            for cell in row {
+                write!(f, "{}", cell.to_char())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
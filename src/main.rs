@@ -2,13 +2,21 @@
 #![allow(clippy::missing_errors_doc)]
 #![allow(clippy::missing_panics_doc)]
 
+pub mod combinatorics;
 pub mod d1;
 pub mod d10;
+pub mod d11;
+pub mod d12;
 pub mod d13;
 pub mod d14;
 pub mod d15;
 pub mod d16;
+pub mod d17;
+pub mod d18;
+pub mod d19;
 pub mod d2;
+pub mod d20;
+pub mod d21;
 pub mod d3;
 pub mod d4;
 pub mod d5;
@@ -16,7 +24,15 @@ pub mod d6;
 pub mod d7;
 pub mod d8;
 pub mod d9;
+pub mod grid;
+pub mod parsing;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    d14::part_one()
+fn main() -> std::process::ExitCode {
+    // Print errors with Display, so parse errors show the offending line
+    if let Err(error) = d14::part_one() {
+        eprintln!("Error: {error}");
+        return std::process::ExitCode::FAILURE;
+    }
+
+    std::process::ExitCode::SUCCESS
 }
@@ -0,0 +1,225 @@
+use std::str::FromStr;
+
+/// The result of parsing something from the start of the input, along with the input left after it
+pub type Parsed<'a, T> = Result<(T, &'a str), &'static str>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Line number, starting from 1
+    pub line: usize,
+    /// Column in chars, starting from 1
+    pub column: usize,
+    pub message: String,
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Creates an error at the 0-based `line` and `column`, where `text` is that line of the input
+    pub fn new(text: &str, line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: line + 1,
+            column: column + 1,
+            message: message.into(),
+            snippet: text.to_owned(),
+        }
+    }
+
+    /// Creates an error at the start of `rest`, which must be a suffix of `input`
+    pub fn at(input: &str, rest: &str, message: impl Into<String>) -> Self {
+        let offset = input.len() - rest.len();
+        let before = &input[..offset];
+
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        let line_end = input[offset..]
+            .find('\n')
+            .map_or(input.len(), |index| offset + index);
+
+        Self::new(
+            &input[line_start..line_end],
+            before.matches('\n').count(),
+            before[line_start..].chars().count(),
+            message,
+        )
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let gutter = self.line.to_string().len();
+
+        writeln!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )?;
+        writeln!(f, "{} | {}", self.line, self.snippet)?;
+        write!(
+            f,
+            "{} | {}^",
+            " ".repeat(gutter),
+            " ".repeat(self.column - 1)
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn digit_count(input: &str) -> usize {
+    input.bytes().take_while(u8::is_ascii_digit).count()
+}
+
+pub fn unsigned<T: FromStr>(input: &str) -> Parsed<'_, T> {
+    unsigned_max_digits(input, usize::MAX)
+}
+
+pub fn unsigned_max_digits<T: FromStr>(input: &str, max_digits: usize) -> Parsed<'_, T> {
+    let digits = digit_count(input).min(max_digits);
+
+    if digits == 0 {
+        return Err("Expected a digit");
+    }
+
+    let (number, rest) = input.split_at(digits);
+
+    let number = number.parse().map_err(|_| "Number does not fit in type")?;
+
+    Ok((number, rest))
+}
+
+/// Parses an integer with an optional leading `-` or `+` from the start of input
+pub fn signed<T: FromStr>(input: &str) -> Parsed<'_, T> {
+    let sign = usize::from(input.starts_with(['-', '+']));
+    let digits = digit_count(&input[sign..]);
+
+    if digits == 0 {
+        return Err("Expected a digit");
+    }
+
+    let (number, rest) = input.split_at(sign + digits);
+
+    let number = number.parse().map_err(|_| "Number does not fit in type")?;
+
+    Ok((number, rest))
+}
+
+pub fn literal<'a>(input: &'a str, literal: &str) -> Result<&'a str, &'static str> {
+    input
+        .strip_prefix(literal)
+        .ok_or("Expected literal was not found")
+}
+
+/// Parses one or more values with `parser`, separated by `separator`
+pub fn separated<'a, T>(
+    input: &'a str,
+    separator: &str,
+    parser: impl Fn(&'a str) -> Parsed<'a, T>,
+) -> Parsed<'a, Vec<T>> {
+    let (first, mut input) = parser(input)?;
+    let mut values = vec![first];
+
+    while let Some(rest) = input.strip_prefix(separator) {
+        let (value, rest) = parser(rest)?;
+        values.push(value);
+        input = rest;
+    }
+
+    Ok((values, input))
+}
+
+pub fn pair<'a, T>(
+    input: &'a str,
+    separator: &str,
+    parser: impl Fn(&'a str) -> Parsed<'a, T>,
+) -> Parsed<'a, (T, T)> {
+    let (first, input) = parser(input)?;
+    let input = literal(input, separator)?;
+    let (second, input) = parser(input)?;
+
+    Ok(((first, second), input))
+}
+
+/// Finds every integer in the line, treating a `-` directly before digits as a sign
+pub fn integers<T: FromStr>(line: &str) -> impl Iterator<Item = T> + '_ {
+    let mut rest = line;
+
+    std::iter::from_fn(move || loop {
+        let start = rest.find(|char: char| char.is_ascii_digit())?;
+        let signed_start = if rest[..start].ends_with('-') {
+            start - 1
+        } else {
+            start
+        };
+        let end = start + digit_count(&rest[start..]);
+
+        // Numbers that don't fit in the type are skipped
+        let number = rest[signed_start..end].parse().ok();
+        rest = &rest[end..];
+
+        if number.is_some() {
+            return number;
+        }
+    })
+}
+
+#[test]
+fn test_numbers() {
+    assert_eq!(unsigned::<u8>("255,"), Ok((255, ",")));
+    assert!(unsigned::<u8>("256").is_err());
+    assert!(unsigned::<u8>("-1").is_err());
+    assert_eq!(
+        unsigned::<u64>("12345678901 rest"),
+        Ok((12_345_678_901, " rest"))
+    );
+    assert_eq!(unsigned_max_digits::<u32>("1234", 3), Ok((123, "4")));
+
+    assert_eq!(signed::<i8>("-128)"), Ok((-128, ")")));
+    assert_eq!(signed::<i8>("+5"), Ok((5, "")));
+    assert!(signed::<i8>("-").is_err());
+    assert!(signed::<i8>("128").is_err());
+}
+
+#[test]
+fn test_combinators() {
+    assert_eq!(literal("p=1", "p="), Ok("1"));
+    assert!(literal("v=1", "p=").is_err());
+
+    assert_eq!(
+        separated("1 2 3: x", " ", unsigned::<u8>),
+        Ok((vec![1, 2, 3], ": x"))
+    );
+    assert!(separated("1 2 x", " ", unsigned::<u8>).is_err());
+
+    assert_eq!(pair("-3,4 v", ",", signed::<i8>), Ok(((-3, 4), " v")));
+}
+
+#[test]
+fn test_integers() {
+    let found: Vec<i32> = integers("p=0,4 v=3,-3 and-7 but not - 8").collect();
+    assert_eq!(found, [0, 4, 3, -3, -7, 8]);
+
+    let found: Vec<u8> = integers("Button A: X+94, Y+34, -1 and 300").collect();
+    assert_eq!(found, [94, 34]);
+}
+
+#[test]
+fn test_parse_error() {
+    let input = "12,3\n4;5\n";
+
+    let (_, rest) = pair(input, ",", unsigned::<u8>).unwrap();
+    let rest = literal(rest, "\n").unwrap();
+    let (_, rest) = unsigned::<u8>(rest).unwrap();
+
+    let error = ParseError::at(input, rest, "Expected ','");
+    assert_eq!((error.line, error.column), (2, 2));
+    assert_eq!(error.snippet, "4;5");
+    assert_eq!(
+        error.to_string(),
+        "line 2, column 2: Expected ','\n2 | 4;5\n  |  ^"
+    );
+
+    let error = ParseError::at(input, "", "Expected more input");
+    assert_eq!(
+        (error.line, error.column, error.snippet.as_str()),
+        (3, 1, "")
+    );
+}